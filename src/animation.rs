@@ -0,0 +1,259 @@
+use std::f64::consts::PI;
+
+use crate::{aabb::Aabb, interval::Interval, transform::Transform, vec3::Vec3};
+
+/// Where and how an object is placed: scaled, then rotated around the x, y
+/// and z axes in that order (in degrees), then translated.
+#[derive(Clone, Copy, Debug)]
+pub struct Pose {
+    pub scale: Vec3,
+    pub rotate: Vec3,
+    pub translate: Vec3,
+}
+
+impl Pose {
+    pub fn transform(&self) -> Transform {
+        Transform::scale(self.scale)
+            .then(&Transform::rotate(Vec3::new(1.0, 0.0, 0.0), self.rotate.x))
+            .then(&Transform::rotate(Vec3::new(0.0, 1.0, 0.0), self.rotate.y))
+            .then(&Transform::rotate(Vec3::new(0.0, 0.0, 1.0), self.rotate.z))
+            .then(&Transform::translate(self.translate))
+    }
+
+    /// Whether every component is finite and no scale factor is zero.
+    pub fn is_valid(&self) -> bool {
+        let finite = |v: Vec3| v.x.is_finite() && v.y.is_finite() && v.z.is_finite();
+        finite(self.scale)
+            && finite(self.rotate)
+            && finite(self.translate)
+            && self.scale.x != 0.0
+            && self.scale.y != 0.0
+            && self.scale.z != 0.0
+    }
+
+    /// Whether a scale factor has a different sign in `other`. Scale is
+    /// interpolated linearly, so between two such poses it passes through
+    /// zero.
+    pub fn flips_scale(&self, other: &Pose) -> bool {
+        let negative = |s: Vec3| [s.x < 0.0, s.y < 0.0, s.z < 0.0];
+        negative(self.scale) != negative(other.scale)
+    }
+}
+
+impl Default for Pose {
+    fn default() -> Self {
+        Pose {
+            scale: Vec3::new(1.0, 1.0, 1.0),
+            rotate: Vec3::new(0.0, 0.0, 0.0),
+            translate: Vec3::new(0.0, 0.0, 0.0),
+        }
+    }
+}
+
+/// A unit quaternion `w + x i + y j + z k` representing a rotation.
+#[derive(Clone, Copy, Debug)]
+struct Quaternion {
+    w: f64,
+    v: Vec3,
+}
+
+impl Quaternion {
+    fn from_axis_angle(axis: Vec3, degrees: f64) -> Self {
+        let (s, c) = (0.5 * degrees.to_radians()).sin_cos();
+        Quaternion { w: c, v: s * axis }
+    }
+
+    /// The rotation `rotate` of a [`Pose`].
+    fn from_euler(rotate: Vec3) -> Self {
+        let x = Quaternion::from_axis_angle(Vec3::new(1.0, 0.0, 0.0), rotate.x);
+        let y = Quaternion::from_axis_angle(Vec3::new(0.0, 1.0, 0.0), rotate.y);
+        let z = Quaternion::from_axis_angle(Vec3::new(0.0, 0.0, 1.0), rotate.z);
+        z.mul(y).mul(x)
+    }
+
+    /// The rotation `other` followed by `self`.
+    fn mul(self, other: Quaternion) -> Quaternion {
+        Quaternion {
+            w: self.w * other.w - self.v.dot(other.v),
+            v: self.w * other.v + other.w * self.v + self.v.cross(other.v),
+        }
+    }
+
+    fn dot(self, other: Quaternion) -> f64 {
+        self.w * other.w + self.v.dot(other.v)
+    }
+
+    /// Spherical interpolation, the short way round.
+    fn slerp(self, other: Quaternion, t: f64) -> Quaternion {
+        let (other, cos_theta) = match self.dot(other) {
+            d if d < 0.0 => (
+                Quaternion {
+                    w: -other.w,
+                    v: -other.v,
+                },
+                -d,
+            ),
+            d => (other, d),
+        };
+
+        let (a, b) = if cos_theta > 0.9995 {
+            // Nearly parallel: interpolate linearly and renormalize.
+            (1.0 - t, t)
+        } else {
+            let theta = cos_theta.acos();
+            let sin_theta = theta.sin();
+            (
+                ((1.0 - t) * theta).sin() / sin_theta,
+                (t * theta).sin() / sin_theta,
+            )
+        };
+
+        let w = a * self.w + b * other.w;
+        let v = a * self.v + b * other.v;
+        let norm = (w * w + v.length_squared()).sqrt();
+        Quaternion {
+            w: w / norm,
+            v: v / norm,
+        }
+    }
+
+    /// The angle between the two rotations, in radians.
+    fn angle_to(self, other: Quaternion) -> f64 {
+        2.0 * self.dot(other).abs().min(1.0).acos()
+    }
+
+    fn transform(self) -> Transform {
+        let sin_half = self.v.length();
+        if sin_half < 1e-12 {
+            return Transform::IDENTITY;
+        }
+        let degrees = 2.0 * sin_half.atan2(self.w) * 180.0 / PI;
+        Transform::rotate(self.v / sin_half, degrees)
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Key {
+    time: f64,
+    scale: Vec3,
+    rotation: Quaternion,
+    translate: Vec3,
+}
+
+impl Key {
+    fn transform(&self) -> Transform {
+        Transform::scale(self.scale)
+            .then(&self.rotation.transform())
+            .then(&Transform::translate(self.translate))
+    }
+}
+
+/// A transform that changes over time, interpolated between keyframes:
+/// scale and translation linearly, rotation along the shortest arc (so turns
+/// of half a revolution or more need keyframes in between). Before the
+/// first keyframe and after the last the pose holds still.
+#[derive(Clone, Debug)]
+pub struct AnimatedTransform {
+    keys: Vec<Key>,
+}
+
+impl AnimatedTransform {
+    /// Builds the animation from `(time, pose)` keyframes in any order.
+    ///
+    /// # Panics
+    ///
+    /// If there are no keyframes, if a time is not finite or a pose is not
+    /// [valid](Pose::is_valid), or if neighbouring keyframes
+    /// [flip a scale factor](Pose::flips_scale).
+    pub fn new(keyframes: impl IntoIterator<Item = (f64, Pose)>) -> Self {
+        let mut keyframes: Vec<(f64, Pose)> = keyframes.into_iter().collect();
+        assert!(
+            !keyframes.is_empty(),
+            "An animation needs at least one keyframe"
+        );
+        assert!(
+            keyframes
+                .iter()
+                .all(|(time, pose)| time.is_finite() && pose.is_valid()),
+            "Keyframes must be finite, with non-zero scale factors"
+        );
+        keyframes.sort_by(|a, b| a.0.total_cmp(&b.0));
+        assert!(
+            keyframes
+                .windows(2)
+                .all(|pair| !pair[0].1.flips_scale(&pair[1].1)),
+            "Scale factors must not change sign between keyframes"
+        );
+
+        let keys = keyframes
+            .into_iter()
+            .map(|(time, pose)| Key {
+                time,
+                scale: pose.scale,
+                rotation: Quaternion::from_euler(pose.rotate),
+                translate: pose.translate,
+            })
+            .collect();
+        AnimatedTransform { keys }
+    }
+
+    pub fn at(&self, time: f64) -> Transform {
+        let next = self.keys.partition_point(|key| key.time <= time);
+        if next == 0 {
+            return self.keys[0].transform();
+        }
+        if next == self.keys.len() {
+            return self.keys[next - 1].transform();
+        }
+
+        let (a, b) = (&self.keys[next - 1], &self.keys[next]);
+        let t = (time - a.time) / (b.time - a.time);
+        Key {
+            time,
+            scale: a.scale + t * (b.scale - a.scale),
+            rotation: a.rotation.slerp(b.rotation, t),
+            translate: a.translate + t * (b.translate - a.translate),
+        }
+        .transform()
+    }
+
+    /// A box enclosing `bbox` at every moment of the animation.
+    pub fn bounding_box(&self, bbox: &Aabb) -> Aabb {
+        const STEPS: usize = 16;
+
+        if bbox.is_empty() {
+            return Aabb::EMPTY;
+        }
+        if !bbox.surface_area().is_finite() {
+            return Aabb::UNIVERSE;
+        }
+
+        let mut bounds = self.keys[0].transform().bounding_box(bbox);
+        for pair in self.keys.windows(2) {
+            let (a, b) = (&pair[0], &pair[1]);
+            for step in 1..=STEPS {
+                let time = a.time + (b.time - a.time) * step as f64 / STEPS as f64;
+                bounds = Aabb::surrounding(&bounds, &self.at(time).bounding_box(bbox));
+            }
+
+            // Between two samples a rotating corner bulges out of their
+            // bounds by at most the sagitta of the arc it travels.
+            let largest_scale = |s: Vec3| s.x.abs().max(s.y.abs()).max(s.z.abs());
+            let reach = largest_scale(a.scale).max(largest_scale(b.scale)) * farthest_corner(bbox);
+            let step_angle = a.rotation.angle_to(b.rotation) / STEPS as f64;
+            let pad = reach * (1.0 - (0.5 * step_angle).cos());
+            bounds = Aabb::new(
+                bounds.x.expand(2.0 * pad),
+                bounds.y.expand(2.0 * pad),
+                bounds.z.expand(2.0 * pad),
+            );
+        }
+        bounds
+    }
+}
+
+/// Distance from the origin to the farthest corner of `bbox`.
+fn farthest_corner(bbox: &Aabb) -> f64 {
+    let far = |i: &Interval| i.min.abs().max(i.max.abs());
+    Vec3::new(far(&bbox.x), far(&bbox.y), far(&bbox.z)).length()
+}
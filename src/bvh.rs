@@ -0,0 +1,285 @@
+use crate::{
+    aabb::Aabb, hit_record::HitRecord, hittable::Hittable, hittable_list::HittableList,
+    interval::Interval, ray::Ray, vec3::Point3,
+};
+
+const BIN_COUNT: usize = 16;
+const MAX_LEAF_SIZE: usize = 4;
+const MAX_DEPTH: usize = 64;
+
+// Cost of visiting a node relative to intersecting one primitive.
+const TRAVERSAL_COST: f64 = 0.5;
+
+struct BvhNode {
+    bbox: Aabb,
+    // Leaves: index of the first object. Interior nodes: index of the right
+    // child (the left child is always stored right after its parent).
+    offset: u32,
+    // Number of objects in a leaf, 0 for interior nodes.
+    count: u32,
+    axis: u8,
+}
+
+struct BuildItem {
+    index: usize,
+    bbox: Aabb,
+    centroid: Point3,
+}
+
+/// A bounding volume hierarchy over a set of objects, built with the binned
+/// surface area heuristic. Objects without finite bounds, such as infinite
+/// planes, are kept outside the tree and tested against every ray.
+pub struct Bvh {
+    objects: Vec<Box<dyn Hittable + Send + Sync>>,
+    nodes: Vec<BvhNode>,
+    unbounded: Vec<Box<dyn Hittable + Send + Sync>>,
+}
+
+impl Bvh {
+    pub fn new(list: HittableList) -> Self {
+        let (unbounded, objects): (Vec<_>, Vec<_>) = list
+            .objects
+            .into_iter()
+            .partition(|object| !object.bounding_box().surface_area().is_finite());
+
+        let mut items: Vec<BuildItem> = objects
+            .iter()
+            .enumerate()
+            .map(|(index, object)| {
+                let bbox = object.bounding_box();
+                BuildItem {
+                    index,
+                    bbox,
+                    centroid: bbox.centroid(),
+                }
+            })
+            .collect();
+
+        let mut nodes = Vec::with_capacity(2 * items.len());
+        if !items.is_empty() {
+            Bvh::build(&mut nodes, &mut items, 0, 0);
+        }
+
+        let mut slots: Vec<Option<Box<dyn Hittable + Send + Sync>>> =
+            objects.into_iter().map(Some).collect();
+        let objects = items
+            .iter()
+            .map(|item| slots[item.index].take().unwrap())
+            .collect();
+
+        Bvh {
+            objects,
+            nodes,
+            unbounded,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.objects.len() + self.unbounded.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn build(
+        nodes: &mut Vec<BvhNode>,
+        items: &mut [BuildItem],
+        offset: usize,
+        depth: usize,
+    ) -> usize {
+        let bbox = items
+            .iter()
+            .fold(Aabb::EMPTY, |b, item| Aabb::surrounding(&b, &item.bbox));
+
+        let node_index = nodes.len();
+        nodes.push(BvhNode {
+            bbox,
+            offset: offset as u32,
+            count: items.len() as u32,
+            axis: 0,
+        });
+
+        if items.len() == 1 || depth >= MAX_DEPTH {
+            return node_index;
+        }
+
+        let centroid_bounds = items.iter().fold(Aabb::EMPTY, |b, item| {
+            Aabb::surrounding(
+                &b,
+                &Aabb::new(
+                    Interval::new(item.centroid.x, item.centroid.x),
+                    Interval::new(item.centroid.y, item.centroid.y),
+                    Interval::new(item.centroid.z, item.centroid.z),
+                ),
+            )
+        });
+
+        let (axis, mid) = match Bvh::sah_split(items, &bbox, &centroid_bounds) {
+            Some(split) => split,
+            None if items.len() <= MAX_LEAF_SIZE => return node_index,
+            None => {
+                // All centroids coincide (or SAH is unusable): fall back to an
+                // object median split so the tree still terminates.
+                let axis = centroid_bounds.longest_axis();
+                items.sort_by(|a, b| a.centroid[axis].total_cmp(&b.centroid[axis]));
+                (axis, items.len() / 2)
+            }
+        };
+
+        let (left, right) = items.split_at_mut(mid);
+        Bvh::build(nodes, left, offset, depth + 1);
+        let right_index = Bvh::build(nodes, right, offset + mid, depth + 1);
+
+        let node = &mut nodes[node_index];
+        node.offset = right_index as u32;
+        node.count = 0;
+        node.axis = axis;
+
+        node_index
+    }
+
+    /// Finds the cheapest binned surface-area-heuristic split and partitions
+    /// `items` around it. Returns `None` when keeping a leaf is cheaper.
+    fn sah_split(
+        items: &mut [BuildItem],
+        bbox: &Aabb,
+        centroid_bounds: &Aabb,
+    ) -> Option<(u8, usize)> {
+        let parent_area = bbox.surface_area();
+        if !parent_area.is_finite() || parent_area <= 0.0 {
+            return None;
+        }
+
+        let mut best: Option<(u8, usize, f64)> = None;
+
+        for axis in 0..3 {
+            let extent = centroid_bounds.axis(axis);
+            if extent.size() <= 0.0 {
+                continue;
+            }
+
+            let mut bins = [(Aabb::EMPTY, 0usize); BIN_COUNT];
+            for item in items.iter() {
+                let b = bin_index(item.centroid[axis], extent);
+                bins[b].0 = Aabb::surrounding(&bins[b].0, &item.bbox);
+                bins[b].1 += 1;
+            }
+
+            let mut right_area = [0.0; BIN_COUNT - 1];
+            let mut right_count = [0usize; BIN_COUNT - 1];
+            let mut acc = (Aabb::EMPTY, 0usize);
+            for i in (1..BIN_COUNT).rev() {
+                acc.0 = Aabb::surrounding(&acc.0, &bins[i].0);
+                acc.1 += bins[i].1;
+                right_area[i - 1] = acc.0.surface_area();
+                right_count[i - 1] = acc.1;
+            }
+
+            let mut acc = (Aabb::EMPTY, 0usize);
+            for i in 0..(BIN_COUNT - 1) {
+                acc.0 = Aabb::surrounding(&acc.0, &bins[i].0);
+                acc.1 += bins[i].1;
+
+                if acc.1 == 0 || right_count[i] == 0 {
+                    continue;
+                }
+
+                let cost = TRAVERSAL_COST
+                    + (acc.0.surface_area() * acc.1 as f64 + right_area[i] * right_count[i] as f64)
+                        / parent_area;
+
+                if best.is_none_or(|(_, _, best_cost)| cost < best_cost) {
+                    best = Some((axis, i, cost));
+                }
+            }
+        }
+
+        let (axis, bin, cost) = best?;
+        if items.len() <= MAX_LEAF_SIZE && cost >= items.len() as f64 {
+            return None;
+        }
+
+        let extent = centroid_bounds.axis(axis);
+        let mut mid = 0;
+        for j in 0..items.len() {
+            if bin_index(items[j].centroid[axis], extent) <= bin {
+                items.swap(mid, j);
+                mid += 1;
+            }
+        }
+
+        Some((axis, mid))
+    }
+}
+
+fn bin_index(c: f64, extent: &Interval) -> usize {
+    let b = ((c - extent.min) / extent.size() * BIN_COUNT as f64) as usize;
+    b.min(BIN_COUNT - 1)
+}
+
+impl Hittable for Bvh {
+    fn hit(&self, r: &Ray, ray_t: &Interval) -> Option<HitRecord> {
+        let mut hit_record: Option<HitRecord> = None;
+        let mut closest_so_far = ray_t.max;
+
+        for object in self.unbounded.iter() {
+            if let Some(hr) = object.hit(r, &Interval::new(ray_t.min, closest_so_far)) {
+                closest_so_far = hr.t;
+                hit_record = Some(hr);
+            }
+        }
+
+        if self.nodes.is_empty() {
+            return hit_record;
+        }
+
+        let mut stack = [0u32; MAX_DEPTH + 2];
+        let mut stack_size = 1;
+
+        while stack_size > 0 {
+            stack_size -= 1;
+            let node_index = stack[stack_size];
+            let node = &self.nodes[node_index as usize];
+
+            if !node.bbox.hit(r, &Interval::new(ray_t.min, closest_so_far)) {
+                continue;
+            }
+
+            if node.count > 0 {
+                let start = node.offset as usize;
+                let end = start + node.count as usize;
+                for object in self.objects[start..end].iter() {
+                    if let Some(hr) = object.hit(r, &Interval::new(ray_t.min, closest_so_far)) {
+                        closest_so_far = hr.t;
+                        hit_record = Some(hr);
+                    }
+                }
+                continue;
+            }
+
+            // Visit the child closer to the ray origin first so later boxes can
+            // be culled against the nearest hit found so far.
+            let left = node_index + 1;
+            let right = node.offset;
+            let (near, far) = if r.dir[node.axis] < 0.0 {
+                (right, left)
+            } else {
+                (left, right)
+            };
+
+            stack[stack_size] = far;
+            stack[stack_size + 1] = near;
+            stack_size += 2;
+        }
+
+        hit_record
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        let bbox = self.nodes.first().map_or(Aabb::EMPTY, |node| node.bbox);
+        self.unbounded.iter().fold(bbox, |b, object| {
+            Aabb::surrounding(&b, &object.bounding_box())
+        })
+    }
+}
@@ -1,71 +1,247 @@
 use crate::{
-    hittable_list::HittableList,
+    film::{Film, PixelStats},
+    filter::Filter,
+    hittable::Hittable,
+    image::Image,
     interval::Interval,
+    material::Materials,
+    progress::{CancelToken, Progress, ProgressCallback, ProgressTracker},
     ray::Ray,
+    sampler::{sample_unit_disk, sample_unit_sphere, Sampler, SamplerKind},
+    tile::{self, TileOrder},
     vec3::{Color, Point3, Vec3},
 };
 
-use rayon::prelude::*;
+use rayon::{BroadcastContext, ThreadPool, ThreadPoolBuildError, ThreadPoolBuilder};
+use serde::Deserialize;
 
-use std::cmp::max;
+use std::{
+    cmp::max,
+    ops::ControlFlow,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+};
+
+#[derive(Clone, Copy, Debug)]
+pub enum Background {
+    /// Blue-white gradient along the ray's vertical direction.
+    Sky,
+    Solid(Color),
+}
+
+impl Background {
+    pub fn color(&self, r: &Ray) -> Color {
+        match self {
+            Background::Sky => {
+                let a = 0.5 * r.dir.unit_vector().y + 1.0;
+                (1.0 - a) * Color::new(1.0, 1.0, 1.0) + a * Color::new(0.5, 0.7, 1.0)
+            }
+            Background::Solid(color) => *color,
+        }
+    }
+}
+
+/// Fog or haze filling the whole scene, the same everywhere. Rays scatter
+/// after travelling an exponentially distributed distance, so none ever
+/// escape to the background: it suits enclosed scenes like a room, while an
+/// outdoor haze is better made with a
+/// [`ConstantMedium`](crate::medium::ConstantMedium) inside a large sphere.
+#[derive(Clone, Copy, Debug)]
+pub struct Atmosphere {
+    /// The chance of scattering per unit of distance travelled. Air with no
+    /// (or a negative) density never scatters.
+    pub density: f64,
+    /// The fraction of light kept at each scattering event.
+    pub albedo: Color,
+}
+
+impl Atmosphere {
+    /// The distance, in units of `r`'s direction, to the next scattering
+    /// event, for a uniform sample `u` in [0, 1), or `None` if the air
+    /// does not scatter.
+    fn scatter_t(&self, r: &Ray, u: f64) -> Option<f64> {
+        (self.density > 0.0).then(|| -(1.0 - u).ln() / (self.density * r.dir.length()))
+    }
+}
 
+/// Settings for adaptive sampling (see [`Camera::adaptive`]).
+///
+/// Every pixel first gets `min_samples` (capped at `samples_per_pixel`, so
+/// keep it well below that to leave budget to redistribute). Pixels whose
+/// estimated error is still above `threshold` then get more samples, up to
+/// `max_samples`, for as long as the render's budget of `samples_per_pixel`
+/// times the pixel count allows. When the budget runs short the noisiest
+/// pixels go first.
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields, default)]
+pub struct AdaptiveSampling {
+    pub min_samples: u32,
+    pub max_samples: u32,
+    /// Standard error of a pixel's mean luminance, relative to that mean,
+    /// below which the pixel counts as converged.
+    pub threshold: f64,
+}
+
+impl Default for AdaptiveSampling {
+    fn default() -> Self {
+        AdaptiveSampling {
+            min_samples: 16,
+            max_samples: 4096,
+            threshold: 0.05,
+        }
+    }
+}
+
+/// A thin-lens camera. The public fields are the render settings; they can be
+/// changed freely between calls to [`Camera::render`].
 pub struct Camera {
     pub aspect_ratio: f64,
     pub image_width: u32,
-
+    pub samples_per_pixel: u32,
+    pub max_depth: u32,
+    pub background: Background,
+    /// A medium filling all of space, or `None` for clear air.
+    pub atmosphere: Option<Atmosphere>,
+
+    pub vfov: f64,
+    pub look_from: Point3,
+    pub look_at: Point3,
+    pub vup: Vec3,
+
+    pub defocus_angle: f64,
+    pub focus_dist: f64,
+
+    /// The times the shutter opens and closes. Each ray gets a time in
+    /// between, so moving objects blur. When they are equal every ray is
+    /// sent at `shutter_open` and no sample is spent on time.
+    pub shutter_open: f64,
+    pub shutter_close: f64,
+
+    /// Seed for all random sampling. Renders with the same seed and settings
+    /// are bit-identical, whatever the thread count.
+    pub seed: u64,
+    /// Pattern for pixel, lens and scattering samples.
+    pub sampler: SamplerKind,
+    /// Spend `samples_per_pixel` on average, but more on noisy pixels. When
+    /// `None`, every pixel gets exactly `samples_per_pixel`.
+    pub adaptive: Option<AdaptiveSampling>,
+
+    /// How samples are weighted into the pixels around them.
+    pub filter: Filter,
+
+    /// Width and height of the square tiles handed to the worker threads.
+    pub tile_size: u32,
+    pub tile_order: TileOrder,
+
+    pool: Option<ThreadPool>,
+    progress: Option<Arc<ProgressCallback>>,
+    cancel: Option<CancelToken>,
+
+    // Derived from the settings above by `initialize`.
     image_height: u32,
     center: Point3,
     pixel00_loc: Vec3,
     pixel_delta_u: Vec3,
     pixel_delta_v: Vec3,
-    samples_per_pixel: u32,
-    max_depth: u32,
-    vfov: f64,
-    look_from: Point3,
-    look_at: Point3,
-    vup: Vec3,
     u: Vec3,
     v: Vec3,
     w: Vec3,
-
-    defocus_angle: f64,
-    focus_dist: f64,
-
     defocus_disk_u: Vec3,
     defocus_disk_v: Vec3,
 }
 
-static EMPTY: Interval = Interval::new(f64::INFINITY, f64::NEG_INFINITY);
-static UNIVERSE: Interval = Interval::new(f64::NEG_INFINITY, f64::INFINITY);
+use std::f64::consts::PI;
 
-static PI: f64 = 3.1415926535897932385;
 fn degrees_to_radians(degrees: f64) -> f64 {
     degrees * PI / 180.0
 }
 
-fn ray_color(r: Ray, depth: u32, world: &HittableList) -> Color {
-    if depth <= 0 {
+/// The radiance arriving along `r`. Adds the number of rays traced to `rays`.
+#[allow(clippy::too_many_arguments)]
+fn ray_color(
+    mut r: Ray,
+    depth: u32,
+    world: &dyn Hittable,
+    materials: &Materials,
+    background: &Background,
+    atmosphere: Option<&Atmosphere>,
+    sampler: &mut dyn Sampler,
+    rays: &mut u64,
+) -> Color {
+    if depth == 0 {
         return Color::new(0.0, 0.0, 0.0);
     }
 
-    let hit_record_option = world.hit(&r, &Interval::new(0.001, f64::INFINITY));
-    if let Some(hit_record) = hit_record_option {
-        let scatter_option = hit_record.material.scatter(&r, &hit_record);
-
-        if let Some((scattered, attenuation)) = scatter_option {
-            return attenuation * ray_color(scattered, depth - 1, world);
+    *rays += 1;
+
+    r.medium_sample = sampler.get_1d();
+    let hit = world.hit(&r, &Interval::new(0.001, f64::INFINITY));
+
+    if let Some(atmosphere) = atmosphere {
+        let t = atmosphere.scatter_t(&r, sampler.get_1d());
+        if let Some(t) = t.filter(|&t| !hit.as_ref().is_some_and(|hit| hit.t <= t)) {
+            let direction = sample_unit_sphere(sampler.get_2d());
+            let scattered = Ray::with_time(r.at(t), direction, r.time);
+            let incoming = ray_color(
+                scattered,
+                depth - 1,
+                world,
+                materials,
+                background,
+                Some(atmosphere),
+                sampler,
+                rays,
+            );
+            return atmosphere.albedo * incoming;
         }
-
-        return Color::new(0.0, 0.0, 0.0);
     }
 
-    let a = 0.5 * r.dir.unit_vector().y + 1.0;
-    let ret = (1.0 - a) * Color::new(1.0, 1.0, 1.0) + a * Color::new(0.5, 0.7, 1.0);
+    let Some(hit_record) = hit else {
+        return background.color(&r);
+    };
+
+    let material = &materials[hit_record.material];
+    let emitted = material.emitted(hit_record.u, hit_record.v, &hit_record.p);
+
+    match material.scatter(&r, &hit_record, sampler) {
+        Some((scattered, attenuation)) => {
+            let incoming = ray_color(
+                scattered,
+                depth - 1,
+                world,
+                materials,
+                background,
+                atmosphere,
+                sampler,
+                rays,
+            );
+            emitted + attenuation * incoming
+        }
+        None => emitted,
+    }
+}
 
-    ret
+impl Default for Camera {
+    fn default() -> Self {
+        Camera::new(
+            1.0,
+            100,
+            10,
+            10,
+            90.0,
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(0.0, 0.0, -1.0),
+            Vec3::new(0.0, 1.0, 0.0),
+            0.0,
+            10.0,
+        )
+    }
 }
 
 impl Camera {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         aspect_ratio: f64,
         image_width: u32,
@@ -78,117 +254,472 @@ impl Camera {
         defocus_angle: f64,
         focus_dist: f64,
     ) -> Self {
-        let image_height: u32 = max(1, (image_width as f64 / aspect_ratio) as u32);
+        let mut camera = Camera {
+            aspect_ratio,
+            image_width,
+            samples_per_pixel,
+            max_depth,
+            background: Background::Sky,
+            atmosphere: None,
+            vfov,
+            look_from,
+            look_at,
+            vup,
+            defocus_angle,
+            focus_dist,
+            shutter_open: 0.0,
+            shutter_close: 0.0,
+            seed: 0,
+            sampler: SamplerKind::default(),
+            adaptive: None,
+            filter: Filter::default(),
+            tile_size: 16,
+            tile_order: TileOrder::default(),
+            pool: None,
+            progress: None,
+            cancel: None,
+            image_height: 0,
+            center: Point3::new(0.0, 0.0, 0.0),
+            pixel00_loc: Vec3::new(0.0, 0.0, 0.0),
+            pixel_delta_u: Vec3::new(0.0, 0.0, 0.0),
+            pixel_delta_v: Vec3::new(0.0, 0.0, 0.0),
+            u: Vec3::new(0.0, 0.0, 0.0),
+            v: Vec3::new(0.0, 0.0, 0.0),
+            w: Vec3::new(0.0, 0.0, 0.0),
+            defocus_disk_u: Vec3::new(0.0, 0.0, 0.0),
+            defocus_disk_v: Vec3::new(0.0, 0.0, 0.0),
+        };
+        camera.initialize();
+        camera
+    }
+
+    /// Image height implied by `image_width` and `aspect_ratio`.
+    pub fn image_height(&self) -> u32 {
+        max(1, (self.image_width as f64 / self.aspect_ratio) as u32)
+    }
+
+    /// Recomputes the viewport from the public settings. `render` calls this,
+    /// so settings can be changed freely between renders.
+    fn initialize(&mut self) {
+        self.image_height = self.image_height();
 
         // Camera
-        let theta = degrees_to_radians(vfov);
+        let theta = degrees_to_radians(self.vfov);
         let h = (theta / 2.0).tan();
-        let viewport_height = 2.0 * h * focus_dist;
-        let real_aspect_ratio = (image_width as f64) / (image_height as f64);
-        let viewport_width = viewport_height * (real_aspect_ratio) as f64;
-        let camera_center = look_from;
+        let viewport_height = 2.0 * h * self.focus_dist;
+        let real_aspect_ratio = (self.image_width as f64) / (self.image_height as f64);
+        let viewport_width = viewport_height * real_aspect_ratio;
+        self.center = self.look_from;
 
-        let w = (look_from - look_at).unit_vector();
-        let u = vup.cross(w).unit_vector();
-        let v = w.cross(u);
+        self.w = (self.look_from - self.look_at).unit_vector();
+        self.u = self.vup.cross(self.w).unit_vector();
+        self.v = self.w.cross(self.u);
 
         // Horizontal edge
-        let viewport_u = u * viewport_width;
+        let viewport_u = self.u * viewport_width;
 
         // Vertical edge
-        let viewport_v = (-v) * viewport_height;
+        let viewport_v = (-self.v) * viewport_height;
 
         // Deltas
-        let pixel_delta_u = viewport_u / image_width as f64;
-        let pixel_delta_v = viewport_v / image_height as f64;
+        self.pixel_delta_u = viewport_u / self.image_width as f64;
+        self.pixel_delta_v = viewport_v / self.image_height as f64;
 
         // Upper-left pixel
         let viewport_upper_left =
-            camera_center - (focus_dist * w) - (0.5 * viewport_u) - (0.5 * viewport_v);
-        let pixel00_loc = viewport_upper_left + 0.5 * (pixel_delta_u + pixel_delta_v);
+            self.center - (self.focus_dist * self.w) - (0.5 * viewport_u) - (0.5 * viewport_v);
+        self.pixel00_loc = viewport_upper_left + 0.5 * (self.pixel_delta_u + self.pixel_delta_v);
 
-        let defocus_radius = focus_dist * degrees_to_radians(defocus_angle / 2.0).tan();
-        let defocus_disk_u = defocus_radius * u;
-        let defocus_disk_v = defocus_radius * v;
+        let defocus_radius = self.focus_dist * degrees_to_radians(self.defocus_angle / 2.0).tan();
+        self.defocus_disk_u = defocus_radius * self.u;
+        self.defocus_disk_v = defocus_radius * self.v;
+    }
 
-        Camera {
-            aspect_ratio,
-            image_width,
-            image_height,
-            center: camera_center,
-            pixel00_loc,
-            pixel_delta_u,
-            pixel_delta_v,
-            samples_per_pixel,
-            max_depth,
-            vfov,
-            look_from,
-            look_at,
-            vup,
-            u,
-            v,
-            w,
-            defocus_angle,
-            focus_dist,
-            defocus_disk_u,
-            defocus_disk_v,
+    /// Traces `samples_per_pixel` paths through every pixel (on average, with
+    /// adaptive sampling) and returns the averaged linear-RGB image.
+    pub fn render(&mut self, world: &(dyn Hittable + Sync), materials: &Materials) -> Image {
+        self.render_film(world, materials).image()
+    }
+
+    /// Like [`Camera::render`], but returns the accumulated [`Film`], which
+    /// also records how many samples each pixel received.
+    pub fn render_film(&mut self, world: &(dyn Hittable + Sync), materials: &Materials) -> Film {
+        self.initialize();
+        let film = Film::new(self.image_width, self.image_height);
+        self.render_passes(world, materials, film, None, |_, _| {
+            ControlFlow::Continue(())
+        })
+    }
+
+    /// Renders in passes of `pass_samples` samples per pixel, calling
+    /// `on_pass` with the pass number (from 1) and the film so far after each
+    /// one. Returning [`ControlFlow::Break`] stops the render early; the film
+    /// is still returned.
+    ///
+    /// With adaptive sampling `pass_samples` is ignored: the passes are the
+    /// ones a single render makes (`min_samples`, then doubling the samples of
+    /// the pixels that are still noisy), so the image is the same either way.
+    pub fn render_progressive(
+        &mut self,
+        world: &(dyn Hittable + Sync),
+        materials: &Materials,
+        pass_samples: u32,
+        on_pass: impl FnMut(u32, &Film) -> ControlFlow<()>,
+    ) -> Film {
+        self.initialize();
+        let film = Film::new(self.image_width, self.image_height);
+        self.render_passes(world, materials, film, Some(pass_samples.max(1)), on_pass)
+    }
+
+    /// Continues a progressive render from a film saved after one of its
+    /// passes (see [`Checkpoint`](crate::checkpoint::Checkpoint)). With the same settings and seed,
+    /// the result is identical to an uninterrupted render; a higher
+    /// `samples_per_pixel` extends the render.
+    ///
+    /// # Panics
+    ///
+    /// If the film's size does not match the camera's image size.
+    pub fn resume_progressive(
+        &mut self,
+        world: &(dyn Hittable + Sync),
+        materials: &Materials,
+        film: Film,
+        pass_samples: u32,
+        on_pass: impl FnMut(u32, &Film) -> ControlFlow<()>,
+    ) -> Film {
+        self.initialize();
+        assert_eq!(
+            (film.width, film.height),
+            (self.image_width, self.image_height),
+            "film size does not match the camera"
+        );
+        self.render_passes(world, materials, film, Some(pass_samples.max(1)), on_pass)
+    }
+
+    /// Adds samples to `film` in passes; `initialize` must have been called.
+    /// Adaptive renders double the samples of the remaining pixels each pass;
+    /// uniform ones take passes of `pass_samples`, or a single pass without
+    /// it. Which pixels are still noisy depends on when it is checked, so the
+    /// adaptive schedule never changes with the pass size. A cancelled
+    /// pass is dropped, so the film always holds whole passes.
+    fn render_passes(
+        &mut self,
+        world: &(dyn Hittable + Sync),
+        materials: &Materials,
+        mut film: Film,
+        pass_samples: Option<u32>,
+        mut on_pass: impl FnMut(u32, &Film) -> ControlFlow<()>,
+    ) -> Film {
+        let pixel_count = film.pixels().len();
+        let budget = self.samples_per_pixel as u64 * pixel_count as u64;
+
+        // Per-pixel cap, and the size of the first pass.
+        let (cap, first_batch) = match &self.adaptive {
+            Some(settings) => {
+                let cap = settings.max_samples.max(1);
+                let min_samples = settings.min_samples.min(self.samples_per_pixel);
+                (cap, min_samples.clamp(1, cap))
+            }
+            None => (self.samples_per_pixel, pass_samples.unwrap_or(u32::MAX)),
+        };
+
+        // Batch size after a pass that left active pixels with `samples`.
+        let next_batch = |samples: u32| match pass_samples {
+            _ if self.adaptive.is_some() => samples,
+            Some(n) => n,
+            None => u32::MAX,
+        };
+
+        // The pixels sampled by the latest pass are the ones still active; on
+        // a fresh film that is all of them. A resumed film may already be at
+        // or past the cap (say, resumed with a lower `samples_per_pixel`), in
+        // which case it is finished.
+        let latest = film.pixels().iter().map(PixelStats::samples).max();
+        let latest = latest.unwrap_or(0);
+        let mut active = (0..pixel_count)
+            .filter(|&p| film.pixels()[p].samples() == latest && latest < cap)
+            .collect::<Vec<usize>>();
+        let mut batch = if latest == 0 {
+            first_batch
+        } else {
+            next_batch(latest)
+        };
+        let mut spent = film.total_samples();
+        let mut pass = 0;
+        let mut tracker = ProgressTracker::new(self.progress.as_deref(), spent, budget);
+
+        let tiles = tile::tiles(film.width, film.height, self.tile_size, self.tile_order);
+        let mut tile_of = vec![0; pixel_count];
+        for (t, tile) in tiles.iter().enumerate() {
+            for y in tile.y..tile.y + tile.height {
+                let row = (y * film.width) as usize;
+                tile_of[row + tile.x as usize..row + (tile.x + tile.width) as usize].fill(t);
+            }
         }
+
+        while !active.is_empty() {
+            // Active pixels have all been sampled equally, so they share the
+            // batch size.
+            let samples = film.pixels()[active[0]].samples();
+            batch = batch.min(cap.saturating_sub(samples));
+            if batch == 0 {
+                break;
+            }
+
+            if let Some(settings) = &self.adaptive {
+                if samples > 0 {
+                    let mut noisy = active
+                        .iter()
+                        .map(|&p| (p, film.pixels()[p].relative_error()))
+                        .filter(|&(_, error)| error > settings.threshold)
+                        .collect::<Vec<(usize, f64)>>();
+
+                    // Noisiest first; the sort is stable, so ties keep pixel
+                    // order and the result stays deterministic.
+                    noisy.sort_by(|a, b| b.1.total_cmp(&a.1));
+                    noisy.truncate((budget.saturating_sub(spent) / batch as u64) as usize);
+
+                    active = noisy.into_iter().map(|(p, _)| p).collect();
+                    if active.is_empty() {
+                        break;
+                    }
+                }
+            }
+
+            // Group this pass's pixels by tile, keeping the tile order.
+            let mut work = vec![Vec::new(); tiles.len()];
+            for &p in &active {
+                work[tile_of[p]].push(p);
+            }
+            work.retain(|pixels| !pixels.is_empty());
+
+            pass += 1;
+            tracker.start_pass(pass, work.len());
+            if !self.sample_pixels(world, materials, &mut film, &work, batch, &tracker) {
+                break;
+            }
+            spent += active.len() as u64 * batch as u64;
+
+            if on_pass(pass, &film).is_break() {
+                break;
+            }
+
+            batch = next_batch(samples + batch);
+        }
+
+        film
     }
 
-    pub fn render(&self, world: &HittableList) -> std::io::Result<()> {
-        println!("P3\n{} {}\n255", self.image_width, self.image_height);
-
-        let rows = (0..self.image_height)
-            .collect::<Vec<u32>>()
-            .par_iter()
-            .map(|j| {
-                (0..self.image_width)
-                    .collect::<Vec<u32>>()
-                    .iter()
-                    .map(|i| {
-                        (0..self.samples_per_pixel)
-                            .collect::<Vec<u32>>()
-                            .iter()
-                            .map(|_| ray_color(self.get_ray(*i, *j), self.max_depth, world))
-                            .reduce(|a, b| a + b)
-                            .unwrap()
-                            .to_color_string(self.samples_per_pixel)
-                    })
-                    .collect::<Vec<String>>()
-                    .join("\n")
-            })
-            .collect::<Vec<String>>()
-            .join("\n");
-
-        println!("{}", rows);
-
-        // std::io::stderr().write_all(format!("Row {}/{}\n", j, self.image_height).as_bytes())?;
+    /// Adds `count` samples to each pixel in `tiles`, continuing each pixel's
+    /// sample sequence where it left off. Every worker thread takes the next
+    /// tile in order until none are left, and progress is reported as each
+    /// tile finishes. Returns false, leaving the film untouched, if the render
+    /// was cancelled.
+    ///
+    /// Splats onto neighbouring pixels are collected per tile and added once
+    /// the pass is done, in tile order, so the sums do not depend on which
+    /// thread finished first.
+    fn sample_pixels(
+        &self,
+        world: &(dyn Hittable + Sync),
+        materials: &Materials,
+        film: &mut Film,
+        tiles: &[Vec<usize>],
+        count: u32,
+        tracker: &ProgressTracker,
+    ) -> bool {
+        let cancelled = || self.cancel.as_ref().is_some_and(CancelToken::is_cancelled);
+        let next_tile = AtomicUsize::new(0);
+        let stats = film.pixels();
+        let filter = self.filter;
+        let margin = filter.margin() as i64;
+
+        let worker = |_: BroadcastContext| {
+            let mut sampler = self.sampler.build(self.seed, self.samples_per_pixel);
+            let mut done = Vec::new();
+            loop {
+                let t = next_tile.fetch_add(1, Ordering::Relaxed);
+                let Some(tile) = tiles.get(t) else {
+                    break;
+                };
+
+                let mut rays = 0;
+                let mut updated = Vec::with_capacity(tile.len());
+                let mut splats = (margin > 0)
+                    .then(|| Splats::around(tile, self.image_width, self.image_height, margin));
+                for &p in tile {
+                    if cancelled() {
+                        return None;
+                    }
+
+                    let (i, j) = (p as u32 % self.image_width, p as u32 / self.image_width);
+                    let mut pixel = stats[p];
+                    for s in pixel.samples()..pixel.samples() + count {
+                        sampler.start_pixel_sample(i, j, s);
+                        let [u, v] = sampler.get_2d();
+                        let offset = [u - 0.5, v - 0.5];
+                        let color = ray_color(
+                            self.get_ray(i, j, offset, sampler.as_mut()),
+                            self.max_depth,
+                            world,
+                            materials,
+                            &self.background,
+                            self.atmosphere.as_ref(),
+                            sampler.as_mut(),
+                            &mut rays,
+                        );
+
+                        pixel.add(color);
+                        pixel.add_weighted(color, filter.evaluate(offset[0], offset[1]));
+                        if let Some(splats) = &mut splats {
+                            splats.add(i as i64, j as i64, offset, color, &filter);
+                        }
+                    }
+                    updated.push((p, pixel));
+                }
+
+                tracker.record(tile.len() as u64 * count as u64, rays);
+                done.push((t, updated, splats));
+            }
+            Some(done)
+        };
+
+        let results = match &self.pool {
+            Some(pool) => pool.broadcast(worker),
+            None => rayon::broadcast(worker),
+        };
+        let Some(results) = results.into_iter().collect::<Option<Vec<_>>>() else {
+            return false;
+        };
+        let mut results = results.into_iter().flatten().collect::<Vec<_>>();
+        results.sort_by_key(|&(t, _, _)| t);
+
+        // Every tile's own pixels first: they were copied from the film
+        // before the pass, so writing them back would undo any splats.
+        let stats = film.pixels_mut();
+        for (_, updated, _) in &results {
+            for &(p, pixel) in updated {
+                stats[p] = pixel;
+            }
+        }
+        for splats in results.into_iter().filter_map(|(_, _, splats)| splats) {
+            splats.apply(film);
+        }
+        true
+    }
 
+    /// Renders on a pool of `threads` worker threads owned by the camera,
+    /// rather than on rayon's global pool.
+    pub fn set_threads(&mut self, threads: usize) -> Result<(), ThreadPoolBuildError> {
+        self.pool = Some(ThreadPoolBuilder::new().num_threads(threads).build()?);
         Ok(())
     }
 
-    fn defocus_disk_sample(&self) -> Point3 {
-        let p = Vec3::random_in_unit_disk();
+    /// Sets a function to call as the render progresses. It is called from
+    /// the worker threads each time a tile is done.
+    pub fn set_progress_callback(&mut self, callback: impl Fn(&Progress) + Send + Sync + 'static) {
+        self.progress = Some(Arc::new(callback));
+    }
+
+    /// Makes renders stop soon after `token` is cancelled. The render returns
+    /// the passes completed so far; work on the current pass is discarded.
+    pub fn set_cancel_token(&mut self, token: CancelToken) {
+        self.cancel = Some(token);
+    }
+
+    fn defocus_disk_sample(&self, sampler: &mut dyn Sampler) -> Point3 {
+        let p = sample_unit_disk(sampler.get_2d());
         self.center + (p.x * self.defocus_disk_u) + (p.y * self.defocus_disk_v)
     }
 
-    fn get_ray(&self, i: u32, j: u32) -> Ray {
+    /// A ray through pixel (`i`, `j`), `offset` pixels from its centre.
+    fn get_ray(&self, i: u32, j: u32, offset: [f64; 2], sampler: &mut dyn Sampler) -> Ray {
         let pixel_center = self.pixel00_loc + (i * self.pixel_delta_u) + (j * self.pixel_delta_v);
-        let pixel_sample = pixel_center + self.pixel_sample_square();
+        let pixel_sample =
+            pixel_center + (offset[0] * self.pixel_delta_u) + (offset[1] * self.pixel_delta_v);
         let ray_origin = if self.defocus_angle <= 0.0 {
             self.center
         } else {
-            self.defocus_disk_sample()
+            self.defocus_disk_sample(sampler)
         };
 
         let ray_direction = pixel_sample - ray_origin;
+        let ray_time = if self.shutter_close > self.shutter_open {
+            self.shutter_open + (self.shutter_close - self.shutter_open) * sampler.get_1d()
+        } else {
+            self.shutter_open
+        };
 
-        Ray::new(ray_origin, ray_direction)
+        Ray::with_time(ray_origin, ray_direction, ray_time)
     }
+}
+
+/// A tile's splats onto the pixels around its own: filter-weighted sums over
+/// the tile's bounding box widened by the filter's margin.
+struct Splats {
+    x0: i64,
+    y0: i64,
+    width: i64,
+    height: i64,
+    image_width: i64,
+    image_height: i64,
+    values: Vec<(Color, f64)>,
+}
 
-    fn pixel_sample_square(&self) -> Vec3 {
-        let px = -0.5 + rand::random::<f64>();
-        let py = -0.5 + rand::random::<f64>();
-        (px * self.pixel_delta_u) + (py * self.pixel_delta_v)
+impl Splats {
+    fn around(tile: &[usize], image_width: u32, image_height: u32, margin: i64) -> Self {
+        let w = image_width as usize;
+        let xs = tile.iter().map(|&p| (p % w) as i64);
+        let ys = tile.iter().map(|&p| (p / w) as i64);
+        let x0 = xs.clone().min().unwrap_or(0) - margin;
+        let x1 = xs.max().unwrap_or(0) + margin;
+        let y0 = ys.clone().min().unwrap_or(0) - margin;
+        let y1 = ys.max().unwrap_or(0) + margin;
+        let (width, height) = (x1 - x0 + 1, y1 - y0 + 1);
+
+        Splats {
+            x0,
+            y0,
+            width,
+            height,
+            image_width: image_width as i64,
+            image_height: image_height as i64,
+            values: vec![(Color::new(0.0, 0.0, 0.0), 0.0); (width * height) as usize],
+        }
+    }
+
+    /// Splats a sample taken `offset` from the centre of pixel (`i`, `j`)
+    /// onto that pixel's neighbours. The pixel itself is left out; its
+    /// stats are updated directly.
+    fn add(&mut self, i: i64, j: i64, offset: [f64; 2], color: Color, filter: &Filter) {
+        let margin = filter.margin() as i64;
+        for y in (j - margin).max(0)..=(j + margin).min(self.image_height - 1) {
+            for x in (i - margin).max(0)..=(i + margin).min(self.image_width - 1) {
+                if (x, y) == (i, j) {
+                    continue;
+                }
+                let weight =
+                    filter.evaluate(offset[0] - (x - i) as f64, offset[1] - (y - j) as f64);
+                if weight != 0.0 {
+                    let (sum, weight_sum) =
+                        &mut self.values[((y - self.y0) * self.width + x - self.x0) as usize];
+                    *sum += weight * color;
+                    *weight_sum += weight;
+                }
+            }
+        }
+    }
+
+    fn apply(self, film: &mut Film) {
+        let stats = film.pixels_mut();
+        for y in self.y0.max(0)..(self.y0 + self.height).min(self.image_height) {
+            for x in self.x0.max(0)..(self.x0 + self.width).min(self.image_width) {
+                let (sum, weight) =
+                    self.values[((y - self.y0) * self.width + x - self.x0) as usize];
+                if weight != 0.0 {
+                    stats[(y * self.image_width + x) as usize].add_weighted_sum(sum, weight);
+                }
+            }
+        }
     }
 }
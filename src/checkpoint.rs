@@ -0,0 +1,590 @@
+use std::{
+    fmt,
+    fs::{self, File},
+    io::{BufWriter, Write},
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    camera::{AdaptiveSampling, Camera},
+    film::{Film, PixelStats},
+    filter::{Filter, FilterKind},
+    sampler::SamplerKind,
+    vec3::Color,
+};
+
+const MAGIC: &[u8; 6] = b"RTCKPT";
+const VERSION: u16 = 4;
+const HEADER_SIZE: usize = 6 + 2 + 4 + 4 + 8 + 1 + 1 + 8 + 1 + 4 + 4 + 8 + 4 + 4 + 4;
+const PIXEL_SIZE: usize = 9 * 8 + 4;
+
+#[derive(Debug)]
+pub enum CheckpointError {
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    /// The file is not a checkpoint, or is truncated or corrupt.
+    Format { path: PathBuf, message: String },
+    /// The checkpoint belongs to a render with different settings.
+    Mismatch { path: PathBuf, message: String },
+}
+
+impl fmt::Display for CheckpointError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CheckpointError::Io { path, source } => write!(f, "{}: {}", path.display(), source),
+            CheckpointError::Format { path, message }
+            | CheckpointError::Mismatch { path, message } => {
+                write!(f, "{}: {}", path.display(), message)
+            }
+        }
+    }
+}
+
+impl std::error::Error for CheckpointError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            CheckpointError::Io { source, .. } => Some(source),
+            CheckpointError::Format { .. } | CheckpointError::Mismatch { .. } => None,
+        }
+    }
+}
+
+/// The settings, besides the image size, that a render must be resumed with
+/// to continue where it stopped.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RenderSettings {
+    pub seed: u64,
+    pub sampler: SamplerKind,
+    pub filter: Filter,
+    pub adaptive: Option<AdaptiveSampling>,
+    /// Samples per pixel in each pass of the progressive render.
+    pub pass_samples: u32,
+    pub max_depth: u32,
+    /// The sample budget the sampler was built with. Only the stratified
+    /// sampler depends on it; with the others a render may be resumed with
+    /// a higher budget to add samples.
+    pub samples_per_pixel: u32,
+}
+
+impl RenderSettings {
+    /// The settings of a progressive render by `camera` in passes of
+    /// `pass_samples`.
+    pub fn new(camera: &Camera, pass_samples: u32) -> Self {
+        RenderSettings {
+            seed: camera.seed,
+            sampler: camera.sampler,
+            filter: camera.filter,
+            adaptive: camera.adaptive,
+            pass_samples,
+            max_depth: camera.max_depth,
+            samples_per_pixel: camera.samples_per_pixel,
+        }
+    }
+
+    /// Whether a render must be resumed with the same `samples_per_pixel`:
+    /// the stratified sampler divides each dimension into that many strata,
+    /// so a different budget changes the samples already taken.
+    fn fixes_samples_per_pixel(&self) -> bool {
+        self.sampler == SamplerKind::Stratified
+    }
+}
+
+impl fmt::Display for RenderSettings {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "seed {}, {} sampler, {} filter of radius {}, ",
+            self.seed,
+            self.sampler.name(),
+            self.filter.kind.name(),
+            self.filter.radius
+        )?;
+        match &self.adaptive {
+            Some(adaptive) => write!(
+                f,
+                "adaptive {} to {} samples at threshold {}, ",
+                adaptive.min_samples, adaptive.max_samples, adaptive.threshold
+            )?,
+            None => write!(f, "uniform sampling, ")?,
+        }
+        write!(
+            f,
+            "passes of {} samples, depth {}, {} samples per pixel",
+            self.pass_samples, self.max_depth, self.samples_per_pixel
+        )
+    }
+}
+
+/// The saved state of a progressive render: the [`Film`] (per-pixel sums and
+/// sample counts) plus the settings it was rendered with.
+///
+/// Every sample's random numbers are derived from the seed, the pixel and the
+/// sample index, so the sample counts are all the random state there is:
+/// resuming with [`Camera::resume_progressive`] continues each pixel's
+/// sequence exactly where it stopped.
+pub struct Checkpoint {
+    pub settings: RenderSettings,
+    pub film: Film,
+}
+
+/// Writes `film` and the settings it is being rendered with to `path`. The
+/// file is written next to `path`, flushed to disk and then renamed into
+/// place, so a crash mid-write leaves the previous checkpoint intact.
+pub fn save(
+    path: impl AsRef<Path>,
+    settings: &RenderSettings,
+    film: &Film,
+) -> Result<(), CheckpointError> {
+    let path = path.as_ref();
+    let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+    let partial = path.with_file_name(format!(".{}.partial", file_name));
+    let io_error = |source| CheckpointError::Io {
+        path: path.to_path_buf(),
+        source,
+    };
+
+    let mut out = BufWriter::new(File::create(&partial).map_err(io_error)?);
+    write_checkpoint(&mut out, settings, film).map_err(io_error)?;
+    let file = out.into_inner().map_err(|e| io_error(e.into_error()))?;
+    file.sync_all().map_err(io_error)?;
+
+    fs::rename(&partial, path).map_err(io_error)
+}
+
+fn write_checkpoint(
+    out: &mut impl Write,
+    settings: &RenderSettings,
+    film: &Film,
+) -> std::io::Result<()> {
+    let sampler = SamplerKind::ALL
+        .iter()
+        .position(|&kind| kind == settings.sampler)
+        .unwrap_or(0) as u8;
+    let filter_kind = FilterKind::ALL
+        .iter()
+        .position(|&kind| kind == settings.filter.kind)
+        .unwrap_or(0) as u8;
+    // Uniform renders are stored with zeroed adaptive settings.
+    let adaptive = settings.adaptive.unwrap_or(AdaptiveSampling {
+        min_samples: 0,
+        max_samples: 0,
+        threshold: 0.0,
+    });
+
+    out.write_all(MAGIC)?;
+    out.write_all(&VERSION.to_le_bytes())?;
+    out.write_all(&film.width.to_le_bytes())?;
+    out.write_all(&film.height.to_le_bytes())?;
+    out.write_all(&settings.seed.to_le_bytes())?;
+    out.write_all(&[sampler, filter_kind])?;
+    out.write_all(&settings.filter.radius.to_le_bytes())?;
+    out.write_all(&[settings.adaptive.is_some() as u8])?;
+    out.write_all(&adaptive.min_samples.to_le_bytes())?;
+    out.write_all(&adaptive.max_samples.to_le_bytes())?;
+    out.write_all(&adaptive.threshold.to_le_bytes())?;
+    out.write_all(&settings.pass_samples.to_le_bytes())?;
+    out.write_all(&settings.max_depth.to_le_bytes())?;
+    out.write_all(&settings.samples_per_pixel.to_le_bytes())?;
+
+    for pixel in film.pixels() {
+        let sum = pixel.sum();
+        for v in [
+            sum.x,
+            sum.y,
+            sum.z,
+            pixel.luminance_sum(),
+            pixel.luminance_sq_sum(),
+        ] {
+            out.write_all(&v.to_le_bytes())?;
+        }
+        out.write_all(&pixel.samples().to_le_bytes())?;
+
+        let weighted_sum = pixel.weighted_sum();
+        for v in [
+            weighted_sum.x,
+            weighted_sum.y,
+            weighted_sum.z,
+            pixel.weight_sum(),
+        ] {
+            out.write_all(&v.to_le_bytes())?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Reads little-endian fields from a byte slice.
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl Reader<'_> {
+    fn take<const N: usize>(&mut self) -> [u8; N] {
+        let (head, rest) = self.bytes.split_at(N);
+        self.bytes = rest;
+        head.try_into().unwrap()
+    }
+
+    fn u32(&mut self) -> u32 {
+        u32::from_le_bytes(self.take())
+    }
+
+    fn u64(&mut self) -> u64 {
+        u64::from_le_bytes(self.take())
+    }
+
+    fn f64(&mut self) -> f64 {
+        f64::from_le_bytes(self.take())
+    }
+}
+
+impl Checkpoint {
+    pub fn load(path: impl AsRef<Path>) -> Result<Checkpoint, CheckpointError> {
+        let path = path.as_ref();
+        let bytes = fs::read(path).map_err(|source| CheckpointError::Io {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        let format_error = |message: String| CheckpointError::Format {
+            path: path.to_path_buf(),
+            message,
+        };
+
+        if bytes.len() < HEADER_SIZE || &bytes[..6] != MAGIC {
+            return Err(format_error("not a checkpoint file".to_string()));
+        }
+
+        let mut reader = Reader { bytes: &bytes[6..] };
+        let version = u16::from_le_bytes(reader.take());
+        if version != VERSION {
+            return Err(format_error(format!(
+                "unsupported checkpoint version {}",
+                version
+            )));
+        }
+
+        let width = reader.u32();
+        let height = reader.u32();
+        let seed = reader.u64();
+        let [sampler] = reader.take();
+        let sampler = *SamplerKind::ALL
+            .get(sampler as usize)
+            .ok_or_else(|| format_error(format!("unknown sampler {}", sampler)))?;
+        let [filter_kind] = reader.take();
+        let kind = *FilterKind::ALL
+            .get(filter_kind as usize)
+            .ok_or_else(|| format_error(format!("unknown filter {}", filter_kind)))?;
+        let filter = Filter {
+            kind,
+            radius: reader.f64(),
+        };
+        let [is_adaptive] = reader.take();
+        let adaptive = AdaptiveSampling {
+            min_samples: reader.u32(),
+            max_samples: reader.u32(),
+            threshold: reader.f64(),
+        };
+        let adaptive = match is_adaptive {
+            0 => None,
+            1 => Some(adaptive),
+            _ => return Err(format_error(format!("bad adaptive flag {}", is_adaptive))),
+        };
+        let pass_samples = reader.u32();
+        let max_depth = reader.u32();
+        let samples_per_pixel = reader.u32();
+
+        let pixel_count = width as u64 * height as u64;
+        let expected = HEADER_SIZE as u64 + pixel_count * PIXEL_SIZE as u64;
+        if bytes.len() as u64 != expected {
+            return Err(format_error(format!(
+                "expected {} bytes for a {}x{} image, found {}",
+                expected,
+                width,
+                height,
+                bytes.len()
+            )));
+        }
+
+        let pixels = (0..pixel_count)
+            .map(|_| {
+                let sum = Color::new(reader.f64(), reader.f64(), reader.f64());
+                let (luminance_sum, luminance_sq_sum) = (reader.f64(), reader.f64());
+                let samples = reader.u32();
+                let weighted_sum = Color::new(reader.f64(), reader.f64(), reader.f64());
+                PixelStats::from_sums(
+                    sum,
+                    luminance_sum,
+                    luminance_sq_sum,
+                    samples,
+                    weighted_sum,
+                    reader.f64(),
+                )
+            })
+            .collect();
+
+        Ok(Checkpoint {
+            settings: RenderSettings {
+                seed,
+                sampler,
+                filter,
+                adaptive,
+                pass_samples,
+                max_depth,
+                samples_per_pixel,
+            },
+            film: Film::from_pixels(width, height, pixels),
+        })
+    }
+
+    /// Checks that resuming with `camera` in passes of `pass_samples`
+    /// continues the same render: the image size and every
+    /// [`RenderSettings`] field must match, except that `samples_per_pixel`
+    /// may change unless the sampler is stratified.
+    pub fn check(
+        &self,
+        camera: &Camera,
+        pass_samples: u32,
+        path: impl AsRef<Path>,
+    ) -> Result<(), CheckpointError> {
+        let found = (self.film.width, self.film.height, self.settings);
+        let mut settings = RenderSettings::new(camera, pass_samples);
+        if !self.settings.fixes_samples_per_pixel() {
+            settings.samples_per_pixel = self.settings.samples_per_pixel;
+        }
+        let expected = (camera.image_width, camera.image_height(), settings);
+
+        if found == expected {
+            return Ok(());
+        }
+
+        let describe = |(width, height, settings): (u32, u32, RenderSettings)| {
+            format!("{}x{}, {}", width, height, settings)
+        };
+        Err(CheckpointError::Mismatch {
+            path: path.as_ref().to_path_buf(),
+            message: format!(
+                "checkpoint is for a different render ({}; this render is {})",
+                describe(found),
+                describe(expected)
+            ),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{ops::ControlFlow, path::PathBuf};
+
+    use super::*;
+    use crate::{
+        hittable_list::HittableList,
+        material::{Material, Materials},
+        sphere::Sphere,
+        vec3::Point3,
+    };
+
+    fn scene() -> (HittableList, Materials) {
+        let mut materials = Materials::new();
+        let red = materials.add(Material::new_lambertian(Color::new(0.7, 0.3, 0.3)));
+        let mut world = HittableList::new();
+        world.add(Box::new(Sphere::new(Point3::new(0.0, 0.0, -1.0), 0.5, red)));
+        (world, materials)
+    }
+
+    fn camera(samples_per_pixel: u32, adaptive: Option<AdaptiveSampling>) -> Camera {
+        let mut camera = Camera::default();
+        camera.image_width = 12;
+        camera.samples_per_pixel = samples_per_pixel;
+        camera.adaptive = adaptive;
+        camera.set_threads(1).unwrap();
+        camera
+    }
+
+    fn adaptive() -> Option<AdaptiveSampling> {
+        Some(AdaptiveSampling {
+            min_samples: 2,
+            max_samples: 16,
+            threshold: 0.01,
+        })
+    }
+
+    /// Every value stored for each pixel, bit for bit.
+    fn contents(film: &Film) -> Vec<(u32, [u64; 9])> {
+        film.pixels()
+            .iter()
+            .map(|p| {
+                let (sum, weighted) = (p.sum(), p.weighted_sum());
+                let values = [
+                    sum.x,
+                    sum.y,
+                    sum.z,
+                    p.luminance_sum(),
+                    p.luminance_sq_sum(),
+                    weighted.x,
+                    weighted.y,
+                    weighted.z,
+                    p.weight_sum(),
+                ];
+                (p.samples(), values.map(f64::to_bits))
+            })
+            .collect()
+    }
+
+    /// A path in the temporary directory that is removed when dropped.
+    struct TempPath(PathBuf);
+
+    impl TempPath {
+        fn new(name: &str) -> Self {
+            let file = format!("checkpoint-test-{}-{}", std::process::id(), name);
+            TempPath(std::env::temp_dir().join(file))
+        }
+    }
+
+    impl Drop for TempPath {
+        fn drop(&mut self) {
+            let _ = fs::remove_file(&self.0);
+        }
+    }
+
+    /// Renders one pass of `pass_samples`, then stops.
+    fn first_pass(camera: &mut Camera, pass_samples: u32) -> Film {
+        let (world, materials) = scene();
+        camera.render_progressive(&world, &materials, pass_samples, |_, _| {
+            ControlFlow::Break(())
+        })
+    }
+
+    fn resume(camera: &mut Camera, film: Film, pass_samples: u32) -> Film {
+        let (world, materials) = scene();
+        camera.resume_progressive(&world, &materials, film, pass_samples, |_, _| {
+            ControlFlow::Continue(())
+        })
+    }
+
+    #[test]
+    fn save_and_load_round_trip() {
+        let path = TempPath::new("round-trip");
+        let mut camera = camera(8, adaptive());
+        let film = first_pass(&mut camera, 4);
+        let settings = RenderSettings::new(&camera, 4);
+
+        save(&path.0, &settings, &film).unwrap();
+        let loaded = Checkpoint::load(&path.0).unwrap();
+
+        assert_eq!(loaded.settings, settings);
+        assert_eq!((loaded.film.width, loaded.film.height), (12, 12));
+        assert_eq!(contents(&loaded.film), contents(&film));
+        loaded.check(&camera, 4, &path.0).unwrap();
+    }
+
+    #[test]
+    fn load_rejects_truncated_file() {
+        let path = TempPath::new("truncated");
+        let mut camera = camera(4, None);
+        let film = first_pass(&mut camera, 4);
+        save(&path.0, &RenderSettings::new(&camera, 4), &film).unwrap();
+
+        let bytes = fs::read(&path.0).unwrap();
+        fs::write(&path.0, &bytes[..bytes.len() - 1]).unwrap();
+        assert!(matches!(
+            Checkpoint::load(&path.0),
+            Err(CheckpointError::Format { .. })
+        ));
+    }
+
+    #[test]
+    fn check_rejects_changed_settings() {
+        let path = TempPath::new("changed");
+        let mut camera = camera(8, adaptive());
+        let film = first_pass(&mut camera, 4);
+        save(&path.0, &RenderSettings::new(&camera, 4), &film).unwrap();
+        let loaded = Checkpoint::load(&path.0).unwrap();
+
+        let mismatch = |camera: &Camera, pass_samples| {
+            matches!(
+                loaded.check(camera, pass_samples, &path.0),
+                Err(CheckpointError::Mismatch { .. })
+            )
+        };
+        assert!(mismatch(&camera, 8));
+
+        let mut other = camera.adaptive.unwrap();
+        other.threshold = 0.02;
+        camera.adaptive = Some(other);
+        assert!(mismatch(&camera, 4));
+
+        camera.adaptive = None;
+        assert!(mismatch(&camera, 4));
+
+        camera.adaptive = adaptive();
+        camera.seed = 1;
+        assert!(mismatch(&camera, 4));
+
+        camera.seed = 0;
+        camera.max_depth += 1;
+        assert!(mismatch(&camera, 4));
+
+        // The sample budget may grow, unless the strata depend on it.
+        camera.max_depth -= 1;
+        camera.samples_per_pixel = 32;
+        assert!(!mismatch(&camera, 4));
+
+        camera.sampler = SamplerKind::Stratified;
+        camera.samples_per_pixel = 8;
+        let film = first_pass(&mut camera, 4);
+        save(&path.0, &RenderSettings::new(&camera, 4), &film).unwrap();
+        let loaded = Checkpoint::load(&path.0).unwrap();
+        loaded.check(&camera, 4, &path.0).unwrap();
+
+        camera.samples_per_pixel = 32;
+        assert!(matches!(
+            loaded.check(&camera, 4, &path.0),
+            Err(CheckpointError::Mismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn resume_matches_uninterrupted_render() {
+        let path = TempPath::new("resume");
+        for adaptive in [None, adaptive()] {
+            let mut camera = camera(8, adaptive);
+            let film = first_pass(&mut camera, 4);
+            save(&path.0, &RenderSettings::new(&camera, 4), &film).unwrap();
+
+            let loaded = Checkpoint::load(&path.0).unwrap();
+            let resumed = resume(&mut camera, loaded.film, 4);
+
+            let (world, materials) = scene();
+            let full = camera.render_film(&world, &materials);
+            assert_eq!(contents(&resumed), contents(&full));
+        }
+    }
+
+    #[test]
+    fn resume_with_fewer_samples_keeps_the_film() {
+        // Resuming with a lower budget, or without the adaptive settings that
+        // let pixels go past it, has nothing left to do.
+        for (adaptive, resume_adaptive) in [(None, None), (adaptive(), None)] {
+            let mut camera = camera(8, adaptive);
+            let (world, materials) = scene();
+            let film = camera.render_film(&world, &materials);
+
+            camera.samples_per_pixel = 4;
+            camera.adaptive = resume_adaptive;
+            let resumed = resume(&mut camera, film.clone(), 4);
+            assert_eq!(contents(&resumed), contents(&film));
+        }
+    }
+
+    #[test]
+    fn resume_with_more_samples_extends_the_render() {
+        let mut camera = camera(4, None);
+        let film = first_pass(&mut camera, 4);
+
+        camera.samples_per_pixel = 8;
+        let resumed = resume(&mut camera, film, 4);
+        assert!(resumed.pixels().iter().all(|p| p.samples() == 8));
+    }
+}
@@ -0,0 +1,389 @@
+use std::{fmt, path::PathBuf, str::FromStr, time::Duration};
+
+use ray_tracing_in_a_weekend_rust::{
+    filter::FilterKind, image_writer::ImageFormat, sampler::SamplerKind, scenes::BUILTIN_SCENES,
+    tile::TileOrder, tonemap::ToneMap,
+};
+
+// Exit codes follow BSD sysexits.h.
+pub const EX_USAGE: u8 = 64;
+pub const EX_DATAERR: u8 = 65;
+pub const EX_NOINPUT: u8 = 66;
+pub const EX_SOFTWARE: u8 = 70;
+pub const EX_CANTCREAT: u8 = 73;
+pub const EX_IOERR: u8 = 74;
+
+/// Pass size used when checkpointing or time-limiting a render that is not
+/// progressive.
+pub const DEFAULT_PASS_SAMPLES: u32 = 16;
+
+pub const USAGE: &str = "\
+Usage: render [OPTIONS] [SCENE]
+
+Renders SCENE, either a TOML scene file or the name of a built-in scene
+(default: random_spheres).
+
+Options:
+  -o, --output <PATH>   Output image; format from the extension
+                        (.png, .ppm, .pfm, .exr) [default: image.png]
+      --width <N>       Image width in pixels
+      --spp <N>         Samples per pixel
+      --depth <N>       Maximum number of ray bounces
+      --threads <N>     Number of worker threads [default: all cores]
+      --tile-size <N>   Render in tiles of N x N pixels [default: from
+                        the scene, else 16]
+      --tile-order <ORDER>
+                        Order tiles are rendered in: scanline, spiral or
+                        hilbert [default: from the scene, else spiral]
+      --seed <N>        Seed for the sampler; the same seed gives the
+                        same image [default: from the scene, else 0]
+      --sampler <NAME>  Sample pattern: independent, stratified, halton
+                        or sobol [default: from the scene, else
+                        independent]
+      --filter <NAME>   Pixel reconstruction filter: box, tent, gaussian,
+                        mitchell or lanczos [default: from the scene,
+                        else box]
+      --filter-radius <R>
+                        Filter radius in pixels [default: from the scene,
+                        else 0.5 for box, 1 for tent, 1.5 for gaussian
+                        and 2 for mitchell and lanczos]
+      --adaptive <T>    Adaptive sampling: --spp becomes the average, and
+                        pixels stop once their relative error is below T
+      --exposure <EV>   Brighten (or, if negative, darken) PNG and PPM
+                        output by EV stops [default: from the scene,
+                        else 0]
+      --tone-map <NAME> Fit highlights into PNG and PPM output: clamp,
+                        reinhard, extended_reinhard, aces or agx
+                        [default: from the scene, else clamp]
+      --white-point <L> Luminance mapped to white by extended_reinhard
+                        [default: from the scene, else 4]
+      --dither          Dither PNG and PPM output to hide banding
+      --sample-map <PATH>
+                        Also write an image of samples taken per pixel
+      --progressive <N> Render in passes of N samples per pixel, updating
+                        the output image after each pass (adaptive renders
+                        keep their own passes)
+      --snapshot-interval <SECONDS>
+                        With --progressive, update the output at most
+                        this often [default: 0, every pass]
+      --checkpoint <PATH>
+                        Save the render state to PATH periodically and at
+                        the end (renders in passes of 16 samples unless
+                        --progressive is given)
+      --checkpoint-interval <SECONDS>
+                        Time between checkpoints [default: 60]
+      --resume          Continue from the --checkpoint file if it exists
+      --time-limit <SECONDS>
+                        Stop after this long and save the passes finished
+                        so far (renders in passes of 16 samples unless
+                        --progressive is given)
+  -q, --quiet           Do not show a progress bar
+      --list-scenes     List the built-in scenes and exit
+  -h, --help            Print this help and exit";
+
+#[derive(Debug)]
+pub struct CliError {
+    pub code: u8,
+    pub message: String,
+}
+
+impl CliError {
+    pub fn new(code: u8, message: impl Into<String>) -> Self {
+        CliError {
+            code,
+            message: message.into(),
+        }
+    }
+
+    pub fn usage(message: impl Into<String>) -> Self {
+        CliError::new(EX_USAGE, message)
+    }
+}
+
+impl fmt::Display for CliError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl std::error::Error for CliError {}
+
+pub struct Options {
+    pub scene: String,
+    pub output: PathBuf,
+    pub width: Option<u32>,
+    pub samples_per_pixel: Option<u32>,
+    pub max_depth: Option<u32>,
+    pub threads: Option<usize>,
+    pub tile_size: Option<u32>,
+    pub tile_order: Option<TileOrder>,
+    pub seed: Option<u64>,
+    pub sampler: Option<SamplerKind>,
+    pub filter: Option<FilterKind>,
+    pub filter_radius: Option<f64>,
+    pub adaptive_threshold: Option<f64>,
+    pub exposure: Option<f64>,
+    pub tone_map: Option<ToneMap>,
+    pub white_point: Option<f64>,
+    pub dither: bool,
+    pub sample_map: Option<PathBuf>,
+    pub progressive: Option<u32>,
+    pub snapshot_interval: Duration,
+    pub checkpoint: Option<PathBuf>,
+    pub checkpoint_interval: Duration,
+    pub resume: bool,
+    pub time_limit: Option<Duration>,
+    pub quiet: bool,
+    pub list_scenes: bool,
+    pub help: bool,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            scene: "random_spheres".to_string(),
+            output: PathBuf::from("image.png"),
+            width: None,
+            samples_per_pixel: None,
+            max_depth: None,
+            threads: None,
+            tile_size: None,
+            tile_order: None,
+            seed: None,
+            sampler: None,
+            filter: None,
+            filter_radius: None,
+            adaptive_threshold: None,
+            exposure: None,
+            tone_map: None,
+            white_point: None,
+            dither: false,
+            sample_map: None,
+            progressive: None,
+            snapshot_interval: Duration::ZERO,
+            checkpoint: None,
+            checkpoint_interval: Duration::from_secs(60),
+            resume: false,
+            time_limit: None,
+            quiet: false,
+            list_scenes: false,
+            help: false,
+        }
+    }
+}
+
+fn positive<T: FromStr + PartialOrd + Default>(option: &str, value: &str) -> Result<T, CliError> {
+    match value.parse::<T>() {
+        Ok(n) if n > T::default() => Ok(n),
+        _ => Err(CliError::usage(format!(
+            "invalid value `{}` for {}: expected a positive integer",
+            value, option
+        ))),
+    }
+}
+
+fn seed(option: &str, value: &str) -> Result<u64, CliError> {
+    value.parse::<u64>().map_err(|_| {
+        CliError::usage(format!(
+            "invalid value `{}` for {}: expected a non-negative integer",
+            value, option
+        ))
+    })
+}
+
+fn threshold(option: &str, value: &str) -> Result<f64, CliError> {
+    match value.parse::<f64>() {
+        Ok(t) if t > 0.0 && t.is_finite() => Ok(t),
+        _ => Err(CliError::usage(format!(
+            "invalid value `{}` for {}: expected a positive number",
+            value, option
+        ))),
+    }
+}
+
+fn stops(option: &str, value: &str) -> Result<f64, CliError> {
+    match value.parse::<f64>() {
+        Ok(ev) if ev.is_finite() => Ok(ev),
+        _ => Err(CliError::usage(format!(
+            "invalid value `{}` for {}: expected a number of stops",
+            value, option
+        ))),
+    }
+}
+
+fn seconds(option: &str, value: &str) -> Result<Duration, CliError> {
+    match value.parse::<f64>() {
+        Ok(seconds) if seconds >= 0.0 && seconds.is_finite() => {
+            Ok(Duration::from_secs_f64(seconds))
+        }
+        _ => Err(CliError::usage(format!(
+            "invalid value `{}` for {}: expected a number of seconds",
+            value, option
+        ))),
+    }
+}
+
+impl Options {
+    /// Parses the arguments following the program name.
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Options, CliError> {
+        let mut options = Options::default();
+        let mut scene: Option<String> = None;
+        let mut args = args.into_iter();
+
+        while let Some(arg) = args.next() {
+            // Accept both `--flag value` and `--flag=value`.
+            let (flag, inline_value) = match arg.split_once('=') {
+                Some((flag, value)) if flag.starts_with("--") => {
+                    (flag.to_string(), Some(value.to_string()))
+                }
+                _ => (arg.clone(), None),
+            };
+
+            let takes_value = matches!(
+                flag.as_str(),
+                "-o" | "--output"
+                    | "--width"
+                    | "--spp"
+                    | "--depth"
+                    | "--threads"
+                    | "--tile-size"
+                    | "--tile-order"
+                    | "--seed"
+                    | "--sampler"
+                    | "--filter"
+                    | "--filter-radius"
+                    | "--adaptive"
+                    | "--exposure"
+                    | "--tone-map"
+                    | "--white-point"
+                    | "--sample-map"
+                    | "--progressive"
+                    | "--snapshot-interval"
+                    | "--checkpoint"
+                    | "--checkpoint-interval"
+                    | "--time-limit"
+            );
+
+            let value = if takes_value {
+                match inline_value.or_else(|| args.next()) {
+                    Some(value) => value,
+                    None => return Err(CliError::usage(format!("{} requires a value", flag))),
+                }
+            } else if inline_value.is_some() {
+                return Err(CliError::usage(format!("{} does not take a value", flag)));
+            } else {
+                String::new()
+            };
+
+            match flag.as_str() {
+                "-o" | "--output" => options.output = PathBuf::from(value),
+                "--width" => options.width = Some(positive(&flag, &value)?),
+                "--spp" => options.samples_per_pixel = Some(positive(&flag, &value)?),
+                "--depth" => options.max_depth = Some(positive(&flag, &value)?),
+                "--threads" => options.threads = Some(positive(&flag, &value)?),
+                "--tile-size" => options.tile_size = Some(positive(&flag, &value)?),
+                "--tile-order" => match TileOrder::from_name(&value) {
+                    Some(order) => options.tile_order = Some(order),
+                    None => {
+                        return Err(CliError::usage(format!(
+                            "unknown tile order `{}` (expected scanline, spiral or hilbert)",
+                            value
+                        )))
+                    }
+                },
+                "--seed" => options.seed = Some(seed(&flag, &value)?),
+                "--filter" => match FilterKind::from_name(&value) {
+                    Some(kind) => options.filter = Some(kind),
+                    None => {
+                        return Err(CliError::usage(format!(
+                        "unknown filter `{}` (expected box, tent, gaussian, mitchell or lanczos)",
+                        value
+                    )))
+                    }
+                },
+                "--filter-radius" => options.filter_radius = Some(threshold(&flag, &value)?),
+                "--adaptive" => options.adaptive_threshold = Some(threshold(&flag, &value)?),
+                "--exposure" => options.exposure = Some(stops(&flag, &value)?),
+                "--tone-map" => match ToneMap::from_name(&value) {
+                    Some(op) => options.tone_map = Some(op),
+                    None => {
+                        return Err(CliError::usage(format!(
+                            "unknown tone map `{}` (expected clamp, reinhard, extended_reinhard, aces or agx)",
+                            value
+                        )))
+                    }
+                },
+                "--white-point" => options.white_point = Some(threshold(&flag, &value)?),
+                "--dither" => options.dither = true,
+                "--sample-map" => options.sample_map = Some(PathBuf::from(value)),
+                "--progressive" => options.progressive = Some(positive(&flag, &value)?),
+                "--snapshot-interval" => options.snapshot_interval = seconds(&flag, &value)?,
+                "--checkpoint" => options.checkpoint = Some(PathBuf::from(value)),
+                "--checkpoint-interval" => options.checkpoint_interval = seconds(&flag, &value)?,
+                "--resume" => options.resume = true,
+                "--time-limit" => options.time_limit = Some(seconds(&flag, &value)?),
+                "-q" | "--quiet" => options.quiet = true,
+                "--sampler" => match SamplerKind::from_name(&value) {
+                    Some(kind) => options.sampler = Some(kind),
+                    None => {
+                        return Err(CliError::usage(format!(
+                        "unknown sampler `{}` (expected independent, stratified, halton or sobol)",
+                        value
+                    )))
+                    }
+                },
+                "--list-scenes" => options.list_scenes = true,
+                "-h" | "--help" => options.help = true,
+                _ if flag.starts_with('-') && flag.len() > 1 => {
+                    return Err(CliError::usage(format!("unknown option `{}`", flag)));
+                }
+                _ => {
+                    if let Some(previous) = &scene {
+                        return Err(CliError::usage(format!(
+                            "more than one scene given (`{}` and `{}`)",
+                            previous, arg
+                        )));
+                    }
+                    scene = Some(arg);
+                }
+            }
+        }
+
+        if let Some(scene) = scene {
+            options.scene = scene;
+        }
+
+        if !options.snapshot_interval.is_zero() && options.progressive.is_none() {
+            return Err(CliError::usage(
+                "--snapshot-interval requires --progressive",
+            ));
+        }
+
+        // Catch a bad extension before spending hours on the render.
+        if !options.help && !options.list_scenes {
+            for path in std::iter::once(&options.output).chain(&options.sample_map) {
+                if ImageFormat::from_path(path).is_none() {
+                    return Err(CliError::usage(format!(
+                        "cannot infer an image format from `{}` (expected .png, .ppm, .pfm or .exr)",
+                        path.display()
+                    )));
+                }
+            }
+        }
+
+        Ok(options)
+    }
+}
+
+pub fn scene_list() -> String {
+    let width = BUILTIN_SCENES
+        .iter()
+        .map(|s| s.name.len())
+        .max()
+        .unwrap_or(0);
+    BUILTIN_SCENES
+        .iter()
+        .map(|s| format!("{:width$}  {}", s.name, s.description, width = width))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
@@ -0,0 +1,192 @@
+use crate::{
+    image::{luminance, Image},
+    vec3::Color,
+};
+
+/// Running sums for one pixel: statistics of the samples taken in it, and
+/// the filter-weighted sum of every sample splatted onto it, including
+/// samples from its neighbours.
+#[derive(Clone, Copy, Debug)]
+pub struct PixelStats {
+    sum: Color,
+    luminance_sum: f64,
+    luminance_sq_sum: f64,
+    samples: u32,
+    weighted_sum: Color,
+    weight_sum: f64,
+}
+
+impl PixelStats {
+    pub fn new() -> Self {
+        PixelStats {
+            sum: Color::new(0.0, 0.0, 0.0),
+            luminance_sum: 0.0,
+            luminance_sq_sum: 0.0,
+            samples: 0,
+            weighted_sum: Color::new(0.0, 0.0, 0.0),
+            weight_sum: 0.0,
+        }
+    }
+
+    /// Rebuilds stats from the raw sums, as saved in a checkpoint.
+    pub fn from_sums(
+        sum: Color,
+        luminance_sum: f64,
+        luminance_sq_sum: f64,
+        samples: u32,
+        weighted_sum: Color,
+        weight_sum: f64,
+    ) -> Self {
+        PixelStats {
+            sum,
+            luminance_sum,
+            luminance_sq_sum,
+            samples,
+            weighted_sum,
+            weight_sum,
+        }
+    }
+
+    pub fn add(&mut self, sample: Color) {
+        let y = luminance(sample);
+        self.sum += sample;
+        self.luminance_sum += y;
+        self.luminance_sq_sum += y * y;
+        self.samples += 1;
+    }
+
+    /// Adds a sample, from this pixel or a neighbour, to the filtered value.
+    pub fn add_weighted(&mut self, sample: Color, weight: f64) {
+        self.weighted_sum += weight * sample;
+        self.weight_sum += weight;
+    }
+
+    /// Adds an already weighted sum of samples and its total weight.
+    pub fn add_weighted_sum(&mut self, weighted_sum: Color, weight_sum: f64) {
+        self.weighted_sum += weighted_sum;
+        self.weight_sum += weight_sum;
+    }
+
+    pub fn samples(&self) -> u32 {
+        self.samples
+    }
+
+    pub fn sum(&self) -> Color {
+        self.sum
+    }
+
+    pub fn luminance_sum(&self) -> f64 {
+        self.luminance_sum
+    }
+
+    pub fn luminance_sq_sum(&self) -> f64 {
+        self.luminance_sq_sum
+    }
+
+    pub fn weighted_sum(&self) -> Color {
+        self.weighted_sum
+    }
+
+    pub fn weight_sum(&self) -> f64 {
+        self.weight_sum
+    }
+
+    /// The mean of the samples taken in this pixel alone.
+    pub fn mean(&self) -> Color {
+        if self.samples == 0 {
+            return Color::new(0.0, 0.0, 0.0);
+        }
+        self.sum / self.samples as f64
+    }
+
+    /// The filtered estimate of the pixel: the weighted mean of the samples
+    /// splatted onto it. Falls back to [`PixelStats::mean`] if negative
+    /// filter lobes have cancelled out the weights.
+    pub fn filtered(&self) -> Color {
+        if self.weight_sum <= 0.0 {
+            return self.mean();
+        }
+        self.weighted_sum / self.weight_sum
+    }
+
+    /// Standard error of the mean luminance, relative to that mean.
+    pub fn relative_error(&self) -> f64 {
+        if self.samples < 2 {
+            return f64::INFINITY;
+        }
+
+        let n = self.samples as f64;
+        let mean = self.luminance_sum / n;
+        let variance = ((self.luminance_sq_sum - self.luminance_sum * mean) / (n - 1.0)).max(0.0);
+
+        // The offset stops nearly black pixels from demanding endless samples.
+        (variance / n).sqrt() / (mean + 0.01)
+    }
+}
+
+impl Default for PixelStats {
+    fn default() -> Self {
+        PixelStats::new()
+    }
+}
+
+/// The accumulation buffer of a render: every sample taken so far, summed per
+/// pixel in full precision. Stored top row first, like [`Image`].
+#[derive(Clone, Debug)]
+pub struct Film {
+    pub width: u32,
+    pub height: u32,
+    pixels: Vec<PixelStats>,
+}
+
+impl Film {
+    pub fn new(width: u32, height: u32) -> Self {
+        Film {
+            width,
+            height,
+            pixels: vec![PixelStats::new(); width as usize * height as usize],
+        }
+    }
+
+    pub fn from_pixels(width: u32, height: u32, pixels: Vec<PixelStats>) -> Self {
+        assert_eq!(pixels.len(), width as usize * height as usize);
+        Film {
+            width,
+            height,
+            pixels,
+        }
+    }
+
+    pub fn pixels(&self) -> &[PixelStats] {
+        &self.pixels
+    }
+
+    pub fn pixels_mut(&mut self) -> &mut [PixelStats] {
+        &mut self.pixels
+    }
+
+    /// The current estimate of the image: each pixel's filtered value.
+    pub fn image(&self) -> Image {
+        let pixels = self.pixels.iter().map(PixelStats::filtered);
+        Image::from_pixels(self.width, self.height, pixels)
+    }
+
+    pub fn sample_counts(&self) -> Vec<u32> {
+        self.pixels.iter().map(PixelStats::samples).collect()
+    }
+
+    pub fn total_samples(&self) -> u64 {
+        self.pixels.iter().map(|p| p.samples as u64).sum()
+    }
+
+    /// The sample counts as a grey image, white being the largest count.
+    pub fn sample_count_image(&self) -> Image {
+        let max = self.pixels.iter().map(PixelStats::samples).max();
+        let max = max.unwrap_or(0).max(1) as f64;
+        let pixels = self.pixels.iter().map(|p| {
+            let v = p.samples as f64 / max;
+            Color::new(v, v, v)
+        });
+        Image::from_pixels(self.width, self.height, pixels)
+    }
+}
@@ -1,25 +1,31 @@
-use crate::{material::Material, ray::Ray, vec3::*};
+use crate::{material::MaterialId, ray::Ray, vec3::*};
 
+/// Where and how a ray hit an object. `normal` always points against the
+/// incoming ray; `front_face` tells whether that is the outward side.
 pub struct HitRecord {
     pub p: Point3,
     pub normal: Vec3,
     pub t: f64,
+    pub u: f64,
+    pub v: f64,
     pub front_face: bool,
-    pub material: Material,
+    pub material: MaterialId,
 }
 
 impl HitRecord {
-    pub fn new(p: Point3, normal: Vec3, t: f64, material: Material) -> Self {
+    pub fn new(p: Point3, normal: Vec3, t: f64, material: MaterialId) -> Self {
         HitRecord {
             p,
             normal,
             t,
+            u: 0.0,
+            v: 0.0,
             front_face: false,
             material,
         }
     }
 
-    pub fn build(t: f64, r: &Ray, outward_normal: &Vec3, material: Material) -> Self {
+    pub fn build(t: f64, r: &Ray, outward_normal: &Vec3, material: MaterialId) -> Self {
         let front_face = r.dir.dot(*outward_normal) < 0.0;
         let normal = match front_face {
             true => *outward_normal,
@@ -30,6 +36,8 @@ impl HitRecord {
             p: r.at(t),
             normal,
             t,
+            u: 0.0,
+            v: 0.0,
             front_face,
             material,
         }
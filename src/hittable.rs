@@ -0,0 +1,11 @@
+use crate::{aabb::Aabb, hit_record::HitRecord, interval::Interval, ray::Ray};
+
+/// Anything a ray can intersect.
+pub trait Hittable {
+    /// Returns the closest intersection with `r` whose `t` lies strictly
+    /// inside `ray_t`.
+    fn hit(&self, r: &Ray, ray_t: &Interval) -> Option<HitRecord>;
+
+    /// A box enclosing the whole object, used to build acceleration structures.
+    fn bounding_box(&self) -> Aabb;
+}
@@ -1,41 +1,53 @@
-use crate::{hit_record::HitRecord, interval::Interval, ray::Ray, sphere::Sphere};
+use crate::{aabb::Aabb, hit_record::HitRecord, hittable::Hittable, interval::Interval, ray::Ray};
 
-#[derive(Clone)]
+/// A flat collection of objects, tested one after another. Wrap it in a
+/// [`Bvh`](crate::bvh::Bvh) for anything but small scenes.
 pub struct HittableList {
-    pub spheres: Vec<Sphere>,
+    pub objects: Vec<Box<dyn Hittable + Send + Sync>>,
+    bbox: Aabb,
 }
 
 impl HittableList {
     pub fn new() -> HittableList {
         HittableList {
-            spheres: Vec::new(),
+            objects: Vec::new(),
+            bbox: Aabb::EMPTY,
         }
     }
 
-    pub fn add_sphere(&mut self, sphere: Sphere) {
-        self.spheres.push(sphere);
+    pub fn add(&mut self, object: Box<dyn Hittable + Send + Sync>) {
+        self.bbox = Aabb::surrounding(&self.bbox, &object.bounding_box());
+        self.objects.push(object);
     }
 
-    pub fn hit(&self, r: &Ray, ray_t: &Interval) -> Option<HitRecord> {
+    pub fn clear(&mut self) {
+        self.objects.clear();
+        self.bbox = Aabb::EMPTY;
+    }
+}
+
+impl Default for HittableList {
+    fn default() -> Self {
+        HittableList::new()
+    }
+}
+
+impl Hittable for HittableList {
+    fn hit(&self, r: &Ray, ray_t: &Interval) -> Option<HitRecord> {
         let mut hit_record: Option<HitRecord> = None;
         let mut closest_so_far = ray_t.max;
 
-        for object in self.spheres.iter() {
-            let hr = object.hit(r, &Interval::new(ray_t.min, closest_so_far));
-            if let Some(_) = hr {
-                hit_record = hr;
-                closest_so_far = hit_record.as_ref().unwrap().t.clone();
+        for object in self.objects.iter() {
+            if let Some(hr) = object.hit(r, &Interval::new(ray_t.min, closest_so_far)) {
+                closest_so_far = hr.t;
+                hit_record = Some(hr);
             }
         }
 
         hit_record
+    }
 
-        /*
-        self.spheres
-            .par_iter()
-            .map(|object| object.hit(r, &Interval::new(ray_t.min, ray_t.max)))
-            .filter_map(|hit| hit)
-            .min_by(|a, b| a.t.total_cmp(&b.t))
-         * */
+    fn bounding_box(&self) -> Aabb {
+        self.bbox
     }
 }
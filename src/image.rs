@@ -0,0 +1,261 @@
+use std::{
+    fmt, fs,
+    io::{BufReader, Cursor},
+    path::{Path, PathBuf},
+};
+
+use crate::vec3::Color;
+
+#[derive(Debug)]
+pub enum ImageError {
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    Decode {
+        path: PathBuf,
+        message: String,
+    },
+    Encode {
+        path: PathBuf,
+        message: String,
+    },
+}
+
+impl fmt::Display for ImageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ImageError::Io { path, source } => write!(f, "{}: {}", path.display(), source),
+            ImageError::Decode { path, message } | ImageError::Encode { path, message } => {
+                write!(f, "{}: {}", path.display(), message)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ImageError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ImageError::Io { source, .. } => Some(source),
+            ImageError::Decode { .. } | ImageError::Encode { .. } => None,
+        }
+    }
+}
+
+pub fn srgb_to_linear(c: f64) -> f64 {
+    if c <= 0.04045 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+pub fn linear_to_srgb(c: f64) -> f64 {
+    if c <= 0.0031308 {
+        12.92 * c
+    } else {
+        1.055 * c.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+/// Rec. 709 relative luminance of a linear color.
+pub fn luminance(c: Color) -> f64 {
+    0.2126 * c.x + 0.7152 * c.y + 0.0722 * c.z
+}
+
+fn to_f32(c: Color) -> [f32; 3] {
+    [c.x as f32, c.y as f32, c.z as f32]
+}
+
+/// A linear-RGB image, stored top row first as `f32` triples. Single
+/// precision is plenty for finished pixels and halves the memory of a
+/// full-size render or texture.
+#[derive(Clone, Debug)]
+pub struct Image {
+    pub width: u32,
+    pub height: u32,
+    pixels: Vec<[f32; 3]>,
+}
+
+impl Image {
+    pub fn new(width: u32, height: u32) -> Self {
+        Image {
+            width,
+            height,
+            pixels: vec![[0.0; 3]; width as usize * height as usize],
+        }
+    }
+
+    /// Loads an 8-bit PNG or a PPM (P3/P6) file. Pixel values are assumed to be
+    /// sRGB encoded and are converted to linear.
+    pub fn load(path: impl AsRef<Path>) -> Result<Image, ImageError> {
+        let path = path.as_ref();
+        let bytes = fs::read(path).map_err(|source| ImageError::Io {
+            path: path.to_path_buf(),
+            source,
+        })?;
+
+        let decode_error = |message: String| ImageError::Decode {
+            path: path.to_path_buf(),
+            message,
+        };
+
+        let extension = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(|e| e.to_ascii_lowercase());
+
+        match extension.as_deref() {
+            Some("png") => Image::decode_png(&bytes).map_err(decode_error),
+            Some("ppm") => Image::decode_ppm(&bytes).map_err(decode_error),
+            _ => Err(decode_error(
+                "unsupported image format (expected .png or .ppm)".to_string(),
+            )),
+        }
+    }
+
+    pub fn from_pixels(width: u32, height: u32, pixels: impl IntoIterator<Item = Color>) -> Self {
+        let pixels: Vec<_> = pixels.into_iter().map(to_f32).collect();
+        assert_eq!(pixels.len(), width as usize * height as usize);
+        Image {
+            width,
+            height,
+            pixels,
+        }
+    }
+
+    pub fn pixels(&self) -> &[[f32; 3]] {
+        &self.pixels
+    }
+
+    pub fn pixel(&self, x: u32, y: u32) -> Color {
+        let [r, g, b] = self.pixels[y as usize * self.width as usize + x as usize];
+        Color::new(r as f64, g as f64, b as f64)
+    }
+
+    pub fn set_pixel(&mut self, x: u32, y: u32, color: Color) {
+        self.pixels[y as usize * self.width as usize + x as usize] = to_f32(color);
+    }
+
+    fn from_srgb8(width: u32, height: u32, channels: usize, data: &[u8]) -> Image {
+        let mut image = Image::new(width, height);
+        for (pixel, texel) in image.pixels.iter_mut().zip(data.chunks_exact(channels)) {
+            let c = |i: usize| srgb_to_linear(texel[i] as f64 / 255.0) as f32;
+            *pixel = if channels < 3 {
+                [c(0), c(0), c(0)]
+            } else {
+                [c(0), c(1), c(2)]
+            };
+        }
+        image
+    }
+
+    fn decode_png(bytes: &[u8]) -> Result<Image, String> {
+        let mut decoder = png::Decoder::new(BufReader::new(Cursor::new(bytes)));
+        decoder.set_transformations(png::Transformations::normalize_to_color8());
+
+        let mut reader = decoder.read_info().map_err(|e| e.to_string())?;
+        let mut buf = vec![0; reader.output_buffer_size().ok_or("image is too large")?];
+        let info = reader.next_frame(&mut buf).map_err(|e| e.to_string())?;
+
+        let channels = match info.color_type {
+            png::ColorType::Grayscale => 1,
+            png::ColorType::GrayscaleAlpha => 2,
+            png::ColorType::Rgb => 3,
+            png::ColorType::Rgba => 4,
+            png::ColorType::Indexed => return Err("unexpected indexed output".to_string()),
+        };
+
+        Ok(Image::from_srgb8(
+            info.width,
+            info.height,
+            channels,
+            &buf[..info.buffer_size()],
+        ))
+    }
+
+    fn decode_ppm(bytes: &[u8]) -> Result<Image, String> {
+        let mut pos = 0;
+
+        // Header fields are whitespace separated and may be interleaved with
+        // `#` comments.
+        let next_field = |pos: &mut usize| -> Result<String, String> {
+            loop {
+                while *pos < bytes.len() && bytes[*pos].is_ascii_whitespace() {
+                    *pos += 1;
+                }
+                if *pos < bytes.len() && bytes[*pos] == b'#' {
+                    while *pos < bytes.len() && bytes[*pos] != b'\n' {
+                        *pos += 1;
+                    }
+                    continue;
+                }
+                break;
+            }
+
+            let start = *pos;
+            while *pos < bytes.len() && !bytes[*pos].is_ascii_whitespace() {
+                *pos += 1;
+            }
+
+            if start == *pos {
+                return Err("unexpected end of file".to_string());
+            }
+            Ok(String::from_utf8_lossy(&bytes[start..*pos]).into_owned())
+        };
+
+        let magic = next_field(&mut pos)?;
+        let number = |pos: &mut usize, what: &str| -> Result<u32, String> {
+            let field = next_field(pos)?;
+            field
+                .parse::<u32>()
+                .map_err(|_| format!("invalid {} `{}`", what, field))
+        };
+
+        let width = number(&mut pos, "width")?;
+        let height = number(&mut pos, "height")?;
+        let max_value = number(&mut pos, "maximum value")?;
+        if max_value == 0 || max_value > 65535 {
+            return Err(format!("invalid maximum value {}", max_value));
+        }
+
+        // Every sample takes at least one byte, which also rules out sizes
+        // that would not fit in memory.
+        let count = (width as u64 * height as u64)
+            .checked_mul(3)
+            .and_then(|count| usize::try_from(count).ok())
+            .filter(|&count| count <= bytes.len() - pos)
+            .ok_or_else(|| format!("{}x{} image is larger than the file", width, height))?;
+        let samples: Vec<u32> = match magic.as_str() {
+            "P3" => (0..count)
+                .map(|_| number(&mut pos, "sample"))
+                .collect::<Result<_, _>>()?,
+            "P6" => {
+                // Exactly one whitespace byte separates the header from the raster.
+                pos += 1;
+                let sample_size = if max_value < 256 { 1 } else { 2 };
+                let raster = bytes
+                    .get(pos..)
+                    .and_then(|rest| rest.get(..count * sample_size))
+                    .ok_or("truncated raster")?;
+                if sample_size == 1 {
+                    raster.iter().map(|&b| b as u32).collect()
+                } else {
+                    raster
+                        .chunks_exact(2)
+                        .map(|b| u16::from_be_bytes([b[0], b[1]]) as u32)
+                        .collect()
+                }
+            }
+            _ => return Err(format!("unsupported PPM type `{}`", magic)),
+        };
+
+        let mut image = Image::new(width, height);
+        for (pixel, rgb) in image.pixels.iter_mut().zip(samples.chunks_exact(3)) {
+            let c = |s: u32| srgb_to_linear(s.min(max_value) as f64 / max_value as f64) as f32;
+            *pixel = [c(rgb[0]), c(rgb[1]), c(rgb[2])];
+        }
+
+        Ok(image)
+    }
+}
@@ -0,0 +1,241 @@
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+    path::Path,
+};
+
+use crate::{
+    image::{Image, ImageError},
+    tonemap::PostProcess,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ImageFormat {
+    /// 8-bit sRGB PNG.
+    Png,
+    /// 8-bit sRGB binary PPM (P6).
+    Ppm,
+    /// 32-bit float linear Portable Float Map.
+    Pfm,
+    /// Uncompressed scanline OpenEXR, linear, with half or full float channels.
+    Exr { half: bool },
+}
+
+impl ImageFormat {
+    /// Picks a format from the file extension. `.exr` files are written as
+    /// half floats.
+    pub fn from_path(path: &Path) -> Option<ImageFormat> {
+        let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+        match extension.as_str() {
+            "png" => Some(ImageFormat::Png),
+            "ppm" => Some(ImageFormat::Ppm),
+            "pfm" => Some(ImageFormat::Pfm),
+            "exr" => Some(ImageFormat::Exr { half: true }),
+            _ => None,
+        }
+    }
+}
+
+/// Saves `image` in the format given by the extension of `path`, with the
+/// default [`PostProcess`] for 8-bit formats.
+pub fn save(image: &Image, path: impl AsRef<Path>) -> Result<(), ImageError> {
+    let path = path.as_ref();
+    let format = ImageFormat::from_path(path).ok_or_else(|| ImageError::Encode {
+        path: path.to_path_buf(),
+        message: "unsupported image format (expected .png, .ppm, .pfm or .exr)".to_string(),
+    })?;
+
+    save_with_format(image, path, format, &PostProcess::default())
+}
+
+/// Saves `image` as `format`. 8-bit formats go through `post` first; float
+/// formats store the linear values as they are.
+pub fn save_with_format(
+    image: &Image,
+    path: impl AsRef<Path>,
+    format: ImageFormat,
+    post: &PostProcess,
+) -> Result<(), ImageError> {
+    let path = path.as_ref();
+    let io_error = |source| ImageError::Io {
+        path: path.to_path_buf(),
+        source,
+    };
+
+    let mut out = BufWriter::new(File::create(path).map_err(io_error)?);
+
+    match format {
+        ImageFormat::Png => write_png(image, post, &mut out).map_err(|e| ImageError::Encode {
+            path: path.to_path_buf(),
+            message: e.to_string(),
+        })?,
+        ImageFormat::Ppm => write_ppm(image, post, &mut out).map_err(io_error)?,
+        ImageFormat::Pfm => write_pfm(image, &mut out).map_err(io_error)?,
+        ImageFormat::Exr { half } => write_exr(image, half, &mut out).map_err(io_error)?,
+    }
+
+    out.flush().map_err(io_error)
+}
+
+fn write_png(
+    image: &Image,
+    post: &PostProcess,
+    out: &mut impl Write,
+) -> Result<(), png::EncodingError> {
+    let mut encoder = png::Encoder::new(out, image.width, image.height);
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder.set_source_srgb(png::SrgbRenderingIntent::Perceptual);
+
+    let mut writer = encoder.write_header()?;
+    writer.write_image_data(&post.srgb8(image))?;
+    writer.finish()
+}
+
+fn write_ppm(image: &Image, post: &PostProcess, out: &mut impl Write) -> std::io::Result<()> {
+    write!(out, "P6\n{} {}\n255\n", image.width, image.height)?;
+    out.write_all(&post.srgb8(image))
+}
+
+fn write_pfm(image: &Image, out: &mut impl Write) -> std::io::Result<()> {
+    // A negative scale marks little-endian data. Rows go bottom to top.
+    write!(out, "PF\n{} {}\n-1.0\n", image.width, image.height)?;
+
+    for y in (0..image.height).rev() {
+        for x in 0..image.width {
+            for c in image.pixels()[(y * image.width + x) as usize] {
+                out.write_all(&c.to_le_bytes())?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn write_attribute(header: &mut Vec<u8>, name: &str, kind: &str, value: &[u8]) {
+    header.extend_from_slice(name.as_bytes());
+    header.push(0);
+    header.extend_from_slice(kind.as_bytes());
+    header.push(0);
+    header.extend_from_slice(&(value.len() as i32).to_le_bytes());
+    header.extend_from_slice(value);
+}
+
+fn write_exr(image: &Image, half: bool, out: &mut impl Write) -> std::io::Result<()> {
+    let (pixel_type, sample_size): (i32, usize) = if half { (1, 2) } else { (2, 4) };
+
+    let mut header = Vec::new();
+    header.extend_from_slice(&[0x76, 0x2f, 0x31, 0x01]);
+    header.extend_from_slice(&2u32.to_le_bytes());
+
+    // Channels must be listed in alphabetical order.
+    let mut channels = Vec::new();
+    for name in ["B", "G", "R"] {
+        channels.extend_from_slice(name.as_bytes());
+        channels.push(0);
+        channels.extend_from_slice(&pixel_type.to_le_bytes());
+        channels.extend_from_slice(&[0, 0, 0, 0]);
+        channels.extend_from_slice(&1i32.to_le_bytes());
+        channels.extend_from_slice(&1i32.to_le_bytes());
+    }
+    channels.push(0);
+
+    let mut window = Vec::new();
+    for v in [0, 0, image.width as i32 - 1, image.height as i32 - 1] {
+        window.extend_from_slice(&v.to_le_bytes());
+    }
+
+    write_attribute(&mut header, "channels", "chlist", &channels);
+    write_attribute(&mut header, "compression", "compression", &[0]);
+    write_attribute(&mut header, "dataWindow", "box2i", &window);
+    write_attribute(&mut header, "displayWindow", "box2i", &window);
+    write_attribute(&mut header, "lineOrder", "lineOrder", &[0]);
+    write_attribute(
+        &mut header,
+        "pixelAspectRatio",
+        "float",
+        &1.0f32.to_le_bytes(),
+    );
+    write_attribute(&mut header, "screenWindowCenter", "v2f", &[0; 8]);
+    write_attribute(
+        &mut header,
+        "screenWindowWidth",
+        "float",
+        &1.0f32.to_le_bytes(),
+    );
+    header.push(0);
+
+    out.write_all(&header)?;
+
+    // Uncompressed files store one scanline per chunk, each prefixed by its y
+    // coordinate and byte count.
+    let line_size = image.width as usize * 3 * sample_size;
+    let chunk_size = 8 + line_size;
+    let table_end = header.len() + 8 * image.height as usize;
+    for y in 0..image.height as usize {
+        out.write_all(&((table_end + y * chunk_size) as u64).to_le_bytes())?;
+    }
+
+    let mut line = Vec::with_capacity(line_size);
+    for y in 0..image.height {
+        line.clear();
+        for channel in [2, 1, 0] {
+            for x in 0..image.width {
+                let c = image.pixels()[(y * image.width + x) as usize][channel];
+                if half {
+                    line.extend_from_slice(&f32_to_f16(c).to_le_bytes());
+                } else {
+                    line.extend_from_slice(&c.to_le_bytes());
+                }
+            }
+        }
+
+        out.write_all(&(y as i32).to_le_bytes())?;
+        out.write_all(&(line_size as i32).to_le_bytes())?;
+        out.write_all(&line)?;
+    }
+
+    Ok(())
+}
+
+/// Converts to IEEE 754 binary16 with round-to-nearest-even.
+fn f32_to_f16(value: f32) -> u16 {
+    let bits = value.to_bits();
+    let sign = ((bits >> 16) & 0x8000) as u16;
+    let exponent = ((bits >> 23) & 0xff) as i32;
+    let mantissa = bits & 0x007f_ffff;
+
+    if exponent == 0xff {
+        // Infinity or NaN (keeping NaNs quiet).
+        let nan = if mantissa != 0 { 0x0200 } else { 0 };
+        return sign | 0x7c00 | nan;
+    }
+
+    let unbiased = exponent - 127 + 15;
+
+    if unbiased >= 0x1f {
+        return sign | 0x7c00;
+    }
+
+    if unbiased <= 0 {
+        if unbiased < -10 {
+            return sign;
+        }
+
+        // Subnormal: shift the mantissa, including its implicit leading one.
+        let mantissa = mantissa | 0x0080_0000;
+        let shift = (14 - unbiased) as u32;
+        let half_mantissa = mantissa >> shift;
+        let remainder = mantissa & ((1 << shift) - 1);
+        let halfway = 1 << (shift - 1);
+        let round_up = remainder > halfway || (remainder == halfway && half_mantissa & 1 == 1);
+        return sign | (half_mantissa + round_up as u32) as u16;
+    }
+
+    let half = ((unbiased as u32) << 10) | (mantissa >> 13);
+    let remainder = mantissa & 0x1fff;
+    let round_up = remainder > 0x1000 || (remainder == 0x1000 && half & 1 == 1);
+
+    // A carry out of the mantissa correctly bumps the exponent (up to infinity).
+    sign | (half + round_up as u32) as u16
+}
@@ -0,0 +1,87 @@
+use std::sync::Arc;
+
+use crate::{
+    aabb::Aabb, animation::AnimatedTransform, hit_record::HitRecord, hittable::Hittable,
+    interval::Interval, ray::Ray, transform::Transform,
+};
+
+/// A transformed copy of a shared object. Rays are moved into the object's
+/// own space, so any number of instances can point at one mesh or BVH and
+/// only the object itself is stored once.
+pub struct Instance {
+    object: Arc<dyn Hittable + Send + Sync>,
+    transform: Transform,
+    animation: Option<AnimatedTransform>,
+    bbox: Aabb,
+}
+
+impl Instance {
+    /// Places `object` in the world with `transform`, which maps object
+    /// space to world space.
+    pub fn new(object: Arc<dyn Hittable + Send + Sync>, transform: Transform) -> Self {
+        let bbox = transform.bounding_box(&object.bounding_box());
+        Instance {
+            object,
+            transform,
+            animation: None,
+            bbox,
+        }
+    }
+
+    /// Places `object` with a transform that follows `animation` over time.
+    /// The bounding box covers the whole motion.
+    pub fn animated(object: Arc<dyn Hittable + Send + Sync>, animation: AnimatedTransform) -> Self {
+        let bbox = animation.bounding_box(&object.bounding_box());
+        Instance {
+            object,
+            transform: Transform::IDENTITY,
+            animation: Some(animation),
+            bbox,
+        }
+    }
+
+    pub fn object(&self) -> &Arc<dyn Hittable + Send + Sync> {
+        &self.object
+    }
+
+    /// The object-to-world transform at `time`.
+    pub fn transform_at(&self, time: f64) -> Transform {
+        match &self.animation {
+            Some(animation) => animation.at(time),
+            None => self.transform,
+        }
+    }
+}
+
+impl Hittable for Instance {
+    fn hit(&self, r: &Ray, ray_t: &Interval) -> Option<HitRecord> {
+        let animated;
+        let transform = match &self.animation {
+            Some(animation) => {
+                animated = animation.at(r.time);
+                &animated
+            }
+            None => &self.transform,
+        };
+
+        // The direction is not renormalized, so `t` means the same distance
+        // along the ray in both spaces.
+        let to_object = transform.inverse_matrix();
+        let local = Ray {
+            orig: to_object.transform_point(r.orig),
+            dir: to_object.transform_vector(r.dir),
+            ..*r
+        };
+
+        let mut rec = self.object.hit(&local, ray_t)?;
+        rec.p = transform.point(rec.p);
+        // The inverse transpose keeps which side the ray came from, so
+        // `front_face` stays valid.
+        rec.normal = transform.normal(rec.normal).unit_vector();
+        Some(rec)
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        self.bbox
+    }
+}
@@ -0,0 +1,95 @@
+//! A CPU path tracer that grew out of *Ray Tracing in One Weekend*.
+//!
+//! Build a world out of [`Hittable`] objects, point a [`Camera`] at it and
+//! render into an [`Image`]. Objects refer to their material by its
+//! [`MaterialId`] in a [`Materials`] table:
+//!
+//! ```
+//! use ray_tracing_in_a_weekend_rust::{
+//!     Bvh, Camera, Color, HittableList, Material, Materials, Point3, Sphere,
+//! };
+//!
+//! let mut materials = Materials::new();
+//! let red = materials.add(Material::new_lambertian(Color::new(0.7, 0.3, 0.3)));
+//!
+//! let mut world = HittableList::new();
+//! world.add(Box::new(Sphere::new(Point3::new(0.0, 0.0, -1.0), 0.5, red)));
+//! let world = Bvh::new(world);
+//!
+//! let mut camera = Camera::default();
+//! camera.image_width = 16;
+//! camera.samples_per_pixel = 4;
+//!
+//! let image = camera.render(&world, &materials);
+//! assert_eq!((image.width, image.height), (16, 16));
+//! ```
+//!
+//! Scenes can also be loaded from TOML files with [`Scene::load`], and images
+//! saved with [`image_writer::save`].
+
+pub mod aabb;
+pub mod animation;
+pub mod bvh;
+pub mod camera;
+pub mod checkpoint;
+pub mod film;
+pub mod filter;
+pub mod hit_record;
+pub mod hittable;
+pub mod hittable_list;
+pub mod image;
+pub mod image_writer;
+pub mod instance;
+pub mod interval;
+pub mod mat4;
+pub mod material;
+pub mod medium;
+pub mod obj;
+pub mod perlin;
+pub mod planar;
+pub mod polynomial;
+pub mod progress;
+pub mod quadric;
+pub mod ray;
+pub mod rng;
+pub mod sampler;
+pub mod scene;
+pub mod scenes;
+pub mod sphere;
+pub mod texture;
+pub mod tile;
+pub mod tonemap;
+pub mod transform;
+pub mod triangle_mesh;
+pub mod vec3;
+
+pub use aabb::Aabb;
+pub use animation::{AnimatedTransform, Pose};
+pub use bvh::Bvh;
+pub use camera::{AdaptiveSampling, Atmosphere, Background, Camera};
+pub use checkpoint::Checkpoint;
+pub use film::Film;
+pub use filter::{Filter, FilterKind};
+pub use hit_record::HitRecord;
+pub use hittable::Hittable;
+pub use hittable_list::HittableList;
+pub use image::Image;
+pub use instance::Instance;
+pub use interval::Interval;
+pub use mat4::Mat4;
+pub use material::{Material, MaterialId, MaterialType, Materials};
+pub use medium::ConstantMedium;
+pub use planar::{Disk, Plane, Quad, Triangle};
+pub use progress::{CancelToken, Progress};
+pub use quadric::{Cone, Cylinder, Hyperboloid, Paraboloid, Torus};
+pub use ray::Ray;
+pub use rng::Rng;
+pub use sampler::{Sampler, SamplerKind};
+pub use scene::Scene;
+pub use sphere::Sphere;
+pub use texture::Texture;
+pub use tile::TileOrder;
+pub use tonemap::{PostProcess, ToneMap};
+pub use transform::Transform;
+pub use triangle_mesh::TriangleMesh;
+pub use vec3::{Color, Point3, Vec3};
@@ -1,99 +1,271 @@
-mod camera;
-mod hit_record;
-mod hittable_list;
-mod interval;
-mod material;
-mod ray;
-mod sphere;
-mod vec3;
-
-use camera::Camera;
-use hittable_list::HittableList;
-use material::Material;
-use vec3::Color;
-
-use crate::sphere::Sphere;
-use crate::vec3::Point3;
-
-static PI: f64 = 3.1415926535897932385;
-
-fn main() -> std::io::Result<()> {
-    // World
-    let mut world: HittableList = HittableList::new();
-
-    let ground_material = Material::new_lambertian(Color::new(0.5, 0.5, 0.5));
-    world.add_sphere(Sphere::new(
-        Point3::new(0.0, -1000.0, 0.0),
-        1000.0,
-        ground_material,
-    ));
-
-    for a in (-11)..11 {
-        for b in (-11)..11 {
-            let choose_mat = rand::random::<f64>();
-            let center = Point3::new(
-                a as f64 + 0.9 * rand::random::<f64>(),
-                0.2,
-                b as f64 + 0.9 * rand::random::<f64>(),
-            );
-
-            if (center - Point3::new(4.0, 0.2, 0.0)).length() > 0.9 {
-                let sphere_material: Material = if choose_mat < 0.8 {
-                    // diffuse
-                    let albedo = Color::random() * Color::random();
-                    Material::new_lambertian(albedo)
-                } else if choose_mat < 0.95 {
-                    // metal
-                    let albedo = Color::random_range(0.5, 1.0);
-                    let fuzz = rand::random::<f64>() * 0.5;
-                    Material::new_metal(albedo, fuzz)
-                } else {
-                    // glass
-                    Material::new_dielectric(1.5)
-                };
-
-                world.add_sphere(Sphere::new(center, 0.2, sphere_material));
+mod cli;
+mod progress_bar;
+
+use std::{
+    fs,
+    io::{ErrorKind, IsTerminal},
+    ops::ControlFlow,
+    path::Path,
+    process::ExitCode,
+    sync::Arc,
+    thread,
+    time::Instant,
+};
+
+use cli::{CliError, Options};
+use progress_bar::ProgressBar;
+use ray_tracing_in_a_weekend_rust::{
+    camera::AdaptiveSampling,
+    checkpoint::{self, Checkpoint, CheckpointError, RenderSettings},
+    image::{Image, ImageError},
+    image_writer::{self, ImageFormat},
+    scene::{Scene, SceneError},
+    scenes, Bvh, CancelToken, Film, Filter, PostProcess,
+};
+
+fn load_scene(name: &str) -> Result<Scene, CliError> {
+    let path = Path::new(name);
+    if !path.exists() {
+        if let Some(builtin) = scenes::find(name) {
+            return Ok((builtin.build)());
+        }
+    }
+
+    Scene::load(path).map_err(|e| {
+        let code = match &e {
+            SceneError::Io { source, .. } if source.kind() == ErrorKind::NotFound => {
+                return CliError::new(
+                    cli::EX_NOINPUT,
+                    format!(
+                        "`{}` is neither a scene file nor a built-in scene (see --list-scenes)",
+                        name
+                    ),
+                );
             }
+            SceneError::Io { .. } => cli::EX_IOERR,
+            SceneError::Parse { .. } | SceneError::Asset { .. } => cli::EX_DATAERR,
+        };
+        CliError::new(code, e.to_string())
+    })
+}
+
+fn run(options: Options) -> Result<(), CliError> {
+    let mut scene = load_scene(&options.scene)?;
+
+    let camera = &mut scene.camera;
+    if let Some(width) = options.width {
+        camera.image_width = width;
+    }
+    if let Some(samples_per_pixel) = options.samples_per_pixel {
+        camera.samples_per_pixel = samples_per_pixel;
+    }
+    if let Some(max_depth) = options.max_depth {
+        camera.max_depth = max_depth;
+    }
+    if let Some(seed) = options.seed {
+        camera.seed = seed;
+    }
+    if let Some(sampler) = options.sampler {
+        camera.sampler = sampler;
+    }
+    if let Some(kind) = options.filter {
+        camera.filter = Filter::new(kind);
+    }
+    if let Some(radius) = options.filter_radius {
+        camera.filter.radius = radius;
+    }
+    if let Some(tile_size) = options.tile_size {
+        camera.tile_size = tile_size;
+    }
+    if let Some(tile_order) = options.tile_order {
+        camera.tile_order = tile_order;
+    }
+    if let Some(threads) = options.threads {
+        camera
+            .set_threads(threads)
+            .map_err(|e| CliError::new(cli::EX_SOFTWARE, e.to_string()))?;
+    }
+    if let Some(threshold) = options.adaptive_threshold {
+        let settings = camera
+            .adaptive
+            .get_or_insert_with(AdaptiveSampling::default);
+        settings.threshold = threshold;
+    }
+
+    // Checkpoints are taken between passes, and a stopped render keeps only
+    // its finished passes, so both of these render in passes.
+    let pass_samples = options.progressive.or((options.checkpoint.is_some()
+        || options.time_limit.is_some())
+    .then_some(cli::DEFAULT_PASS_SAMPLES));
+    let settings = RenderSettings::new(camera, pass_samples.unwrap_or_default());
+
+    let resumed = match &options.checkpoint {
+        Some(path) if options.resume && path.exists() => {
+            let checkpoint = Checkpoint::load(path).map_err(checkpoint_error)?;
+            checkpoint
+                .check(camera, settings.pass_samples, path)
+                .map_err(checkpoint_error)?;
+            Some(checkpoint.film)
         }
+        _ => None,
+    };
+
+    let progress_bar = (!options.quiet && std::io::stderr().is_terminal()).then(|| {
+        let bar = Arc::new(ProgressBar::new());
+        let callback_bar = Arc::clone(&bar);
+        camera.set_progress_callback(move |progress| callback_bar.update(progress));
+        bar
+    });
+
+    let cancel = CancelToken::new();
+    if let Some(limit) = options.time_limit {
+        camera.set_cancel_token(cancel.clone());
+        let cancel = cancel.clone();
+        thread::spawn(move || {
+            thread::sleep(limit);
+            cancel.cancel();
+        });
     }
 
-    let material1 = Material::new_dielectric(1.5);
-    world.add_sphere(Sphere::new(Point3::new(0.0, 1.0, 0.0), 1.0, material1));
-
-    let material2 = Material::new_lambertian(Color::new(0.4, 0.2, 0.1));
-    world.add_sphere(Sphere::new(Point3::new(-4.0, 1.0, 0.0), 1.0, material2));
-
-    let material3 = Material::new_metal(Color::new(0.7, 0.6, 0.5), 0.0);
-    world.add_sphere(Sphere::new(Point3::new(4.0, 1.0, 0.0), 1.0, material3));
-
-    // Camera
-    let aspect_ratio = 16.0 / 9.0;
-    let image_width: u32 = 1200;
-    let samples_per_pixel: u32 = 500;
-    let max_depth: u32 = 50;
-    let vfov: f64 = 20.0;
-    let look_from = Point3::new(13.0, 2.0, 3.0);
-    let look_at = Point3::new(0.0, 0.0, 0.0);
-    let vup = Point3::new(0.0, 1.0, 0.0);
-
-    let defocus_angle = 0.6;
-    let focus_dist = 10.0;
-
-    let camera = Camera::new(
-        aspect_ratio,
-        image_width,
-        samples_per_pixel,
-        max_depth,
-        vfov,
-        look_from,
-        look_at,
-        vup,
-        defocus_angle,
-        focus_dist,
-    );
+    let world = Bvh::new(scene.world);
+    let materials = scene.materials;
+
+    let mut post = scene.post;
+    if let Some(exposure) = options.exposure {
+        post.exposure = exposure;
+    }
+    if let Some(tone_map) = options.tone_map {
+        post.tone_map = tone_map;
+    }
+    if let Some(white_point) = options.white_point {
+        post.white_point = white_point;
+    }
+    post.dither |= options.dither;
 
     // Render
-    camera.render(&world)?;
+    let render = || {
+        let Some(pass_samples) = pass_samples else {
+            return Ok(camera.render_film(&world, &materials));
+        };
+
+        let mut last_snapshot = Instant::now();
+        let mut last_checkpoint = Instant::now();
+        let mut error = None;
+        let mut on_pass = |_: u32, film: &Film| {
+            let mut result = Ok(());
+            if options.progressive.is_some() && last_snapshot.elapsed() >= options.snapshot_interval
+            {
+                last_snapshot = Instant::now();
+                result = save_snapshot(&film.image(), &options.output, &post);
+            }
+            if let (Ok(()), Some(path)) = (&result, &options.checkpoint) {
+                if last_checkpoint.elapsed() >= options.checkpoint_interval {
+                    last_checkpoint = Instant::now();
+                    result = checkpoint::save(path, &settings, film).map_err(checkpoint_error);
+                }
+            }
+            match result {
+                Ok(()) => ControlFlow::Continue(()),
+                Err(e) => {
+                    error = Some(e);
+                    ControlFlow::Break(())
+                }
+            }
+        };
+
+        let film = match resumed {
+            Some(film) => {
+                camera.resume_progressive(&world, &materials, film, pass_samples, &mut on_pass)
+            }
+            None => camera.render_progressive(&world, &materials, pass_samples, &mut on_pass),
+        };
+        error.map_or(Ok(film), Err)
+    };
+
+    let film = render();
+
+    if let Some(bar) = &progress_bar {
+        bar.finish();
+    }
+    let film = film?;
+
+    if cancel.is_cancelled() {
+        eprintln!(
+            "render: time limit reached; saving {} of {} samples",
+            film.total_samples(),
+            camera.samples_per_pixel as u64 * film.width as u64 * film.height as u64
+        );
+    }
+
+    if let Some(path) = &options.checkpoint {
+        checkpoint::save(path, &settings, &film).map_err(checkpoint_error)?;
+    }
+
+    save(&film.image(), &options.output, &post)?;
+
+    if let Some(path) = &options.sample_map {
+        save(&film.sample_count_image(), path, &PostProcess::default())?;
+    }
 
     Ok(())
 }
+
+fn checkpoint_error(e: CheckpointError) -> CliError {
+    let code = match &e {
+        CheckpointError::Io { .. } => cli::EX_IOERR,
+        CheckpointError::Format { .. } | CheckpointError::Mismatch { .. } => cli::EX_DATAERR,
+    };
+    CliError::new(code, e.to_string())
+}
+
+/// Writes to a temporary file next to `path` and renames it into place, so
+/// image viewers never see a half-written snapshot.
+fn save_snapshot(image: &Image, path: &Path, post: &PostProcess) -> Result<(), CliError> {
+    let format = ImageFormat::from_path(path).expect("output format is checked by the CLI");
+    let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+    let partial = path.with_file_name(format!(".{}.partial", file_name));
+
+    image_writer::save_with_format(image, &partial, format, post)
+        .map_err(|e| CliError::new(cli::EX_CANTCREAT, e.to_string()))?;
+    fs::rename(&partial, path)
+        .map_err(|e| CliError::new(cli::EX_CANTCREAT, format!("{}: {}", path.display(), e)))
+}
+
+fn save(image: &Image, path: &Path, post: &PostProcess) -> Result<(), CliError> {
+    let format = ImageFormat::from_path(path).expect("output format is checked by the CLI");
+    image_writer::save_with_format(image, path, format, post).map_err(|e| {
+        let code = match &e {
+            ImageError::Io { .. } => cli::EX_CANTCREAT,
+            _ => cli::EX_SOFTWARE,
+        };
+        CliError::new(code, e.to_string())
+    })
+}
+
+fn main() -> ExitCode {
+    let options = match Options::parse(std::env::args().skip(1)) {
+        Ok(options) => options,
+        Err(e) => {
+            eprintln!("render: {}\nTry `render --help` for more information.", e);
+            return ExitCode::from(e.code);
+        }
+    };
+
+    if options.help {
+        println!("{}", cli::USAGE);
+        return ExitCode::SUCCESS;
+    }
+
+    if options.list_scenes {
+        println!("{}", cli::scene_list());
+        return ExitCode::SUCCESS;
+    }
+
+    match run(options) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("render: {}", e);
+            ExitCode::from(e.code)
+        }
+    }
+}
@@ -1,69 +1,146 @@
+use std::{ops::Index, sync::Arc};
+
 use crate::{
     hit_record::HitRecord,
     ray::Ray,
-    vec3::{Color, Vec3},
+    sampler::{sample_unit_sphere, Sampler},
+    texture::{SolidColor, Texture},
+    vec3::{Color, Point3, Vec3},
 };
 
-#[derive(Clone)]
-enum MaterialType {
+/// How a [`Material`] scatters (or emits) light.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MaterialType {
+    /// Ideal diffuse reflector tinted by `albedo`.
     Lambertian,
+    /// Mirror reflection tinted by `albedo` and blurred by `fuzz`.
     Metal,
+    /// Glass-like refraction with index of refraction `ir`.
     Dielectric,
+    /// Emits `emit` and does not scatter.
+    DiffuseLight,
+    /// Scatters uniformly in all directions, tinted by `albedo`. The phase
+    /// function of a [`ConstantMedium`](crate::medium::ConstantMedium).
+    Isotropic,
+}
+
+fn black() -> Arc<dyn Texture + Send + Sync> {
+    Arc::new(SolidColor::new(Color::new(0.0, 0.0, 0.0)))
 }
 
+/// A surface material. Build one with the `new_*` constructors; the type is
+/// fixed at construction and only the parameters it uses are meaningful.
 #[derive(Clone)]
 pub struct Material {
-    pub albedo: Color,
+    pub albedo: Arc<dyn Texture + Send + Sync>,
     pub fuzz: f64,
     pub ir: f64,
-    pub material_type: MaterialType,
+    pub emit: Arc<dyn Texture + Send + Sync>,
+    material_type: MaterialType,
 }
 
 impl Material {
+    pub fn material_type(&self) -> MaterialType {
+        self.material_type
+    }
+
     pub fn new_lambertian(color: Color) -> Self {
+        Material::new_lambertian_texture(Arc::new(SolidColor::new(color)))
+    }
+
+    pub fn new_lambertian_texture(texture: Arc<dyn Texture + Send + Sync>) -> Self {
         Material {
-            albedo: color,
+            albedo: texture,
             fuzz: 0.0,
             ir: 0.0,
+            emit: black(),
             material_type: MaterialType::Lambertian,
         }
     }
 
     pub fn new_metal(color: Color, fuzz: f64) -> Self {
+        Material::new_metal_texture(Arc::new(SolidColor::new(color)), fuzz)
+    }
+
+    pub fn new_metal_texture(texture: Arc<dyn Texture + Send + Sync>, fuzz: f64) -> Self {
         Material {
-            albedo: color,
+            albedo: texture,
             fuzz,
             ir: 0.0,
+            emit: black(),
             material_type: MaterialType::Metal,
         }
     }
 
     pub fn new_dielectric(ir: f64) -> Self {
         Material {
-            albedo: Color::new(1.0, 1.0, 1.0),
+            albedo: Arc::new(SolidColor::new(Color::new(1.0, 1.0, 1.0))),
             fuzz: 0.0,
             ir,
+            emit: black(),
             material_type: MaterialType::Dielectric,
         }
     }
 
-    fn scatter_lambertian(&self, r_in: &Ray, rec: &HitRecord) -> Option<(Ray, Color)> {
-        let mut scatter_direction = rec.normal + Vec3::random_unit_vector();
+    pub fn new_diffuse_light(emit: Color) -> Self {
+        Material::new_diffuse_light_texture(Arc::new(SolidColor::new(emit)))
+    }
+
+    pub fn new_diffuse_light_texture(emit: Arc<dyn Texture + Send + Sync>) -> Self {
+        Material {
+            albedo: black(),
+            fuzz: 0.0,
+            ir: 0.0,
+            emit,
+            material_type: MaterialType::DiffuseLight,
+        }
+    }
+
+    pub fn new_isotropic(color: Color) -> Self {
+        Material::new_isotropic_texture(Arc::new(SolidColor::new(color)))
+    }
+
+    pub fn new_isotropic_texture(texture: Arc<dyn Texture + Send + Sync>) -> Self {
+        Material {
+            albedo: texture,
+            fuzz: 0.0,
+            ir: 0.0,
+            emit: black(),
+            material_type: MaterialType::Isotropic,
+        }
+    }
+
+    fn scatter_lambertian(
+        &self,
+        r_in: &Ray,
+        rec: &HitRecord,
+        sampler: &mut dyn Sampler,
+    ) -> Option<(Ray, Color)> {
+        let mut scatter_direction = rec.normal + sample_unit_sphere(sampler.get_2d());
 
         if scatter_direction.near_zero() {
             scatter_direction = rec.normal;
         }
 
-        let scattered = Ray::new(rec.p, scatter_direction);
-        let attenuation = self.albedo.clone();
+        let scattered = Ray::with_time(rec.p, scatter_direction, r_in.time);
+        let attenuation = self.albedo.value(rec.u, rec.v, &rec.p);
 
         Some((scattered, attenuation))
     }
 
-    fn scatter_metal(&self, r_in: &Ray, rec: &HitRecord) -> Option<(Ray, Color)> {
+    fn scatter_metal(
+        &self,
+        r_in: &Ray,
+        rec: &HitRecord,
+        sampler: &mut dyn Sampler,
+    ) -> Option<(Ray, Color)> {
         let reflected = r_in.dir.unit_vector().reflect(&rec.normal);
-        let scattered = Ray::new(rec.p, reflected + self.fuzz * Color::random_unit_vector());
-        let attenuation = self.albedo;
+        let scattered = Ray::with_time(
+            rec.p,
+            reflected + self.fuzz * sample_unit_sphere(sampler.get_2d()),
+            r_in.time,
+        );
+        let attenuation = self.albedo.value(rec.u, rec.v, &rec.p);
 
         if scattered.dir.dot(rec.normal) > 0.0 {
             return Some((scattered, attenuation));
@@ -72,7 +149,12 @@ impl Material {
         None
     }
 
-    fn scatter_dielectric(&self, r_in: &Ray, rec: &HitRecord) -> Option<(Ray, Vec3)> {
+    fn scatter_dielectric(
+        &self,
+        r_in: &Ray,
+        rec: &HitRecord,
+        sampler: &mut dyn Sampler,
+    ) -> Option<(Ray, Vec3)> {
         let attenuation = Vec3::new(1.0, 1.0, 1.0);
         let refraction_ratio = if rec.front_face {
             1.0 / self.ir
@@ -88,21 +170,48 @@ impl Material {
         let cannot_refract = refraction_ratio * sin_theta > 1.0;
 
         if cannot_refract
-            || Material::dielectric_reflectance(cos_theta, refraction_ratio) > rand::random()
+            || Material::dielectric_reflectance(cos_theta, refraction_ratio) > sampler.get_1d()
         {
             let reflected = unit_direction.reflect(&rec.normal);
-            return Some((Ray::new(rec.p, reflected), attenuation));
+            return Some((Ray::with_time(rec.p, reflected, r_in.time), attenuation));
         }
 
         let refracted = unit_direction.refract(&rec.normal, refraction_ratio);
-        Some((Ray::new(rec.p, refracted), attenuation))
+        Some((Ray::with_time(rec.p, refracted, r_in.time), attenuation))
     }
 
-    pub fn scatter(&self, r_in: &Ray, rec: &HitRecord) -> Option<(Ray, Color)> {
+    fn scatter_isotropic(
+        &self,
+        r_in: &Ray,
+        rec: &HitRecord,
+        sampler: &mut dyn Sampler,
+    ) -> Option<(Ray, Color)> {
+        let scattered = Ray::with_time(rec.p, sample_unit_sphere(sampler.get_2d()), r_in.time);
+        let attenuation = self.albedo.value(rec.u, rec.v, &rec.p);
+
+        Some((scattered, attenuation))
+    }
+
+    /// Samples an outgoing ray, drawing any random decisions from `sampler`.
+    pub fn scatter(
+        &self,
+        r_in: &Ray,
+        rec: &HitRecord,
+        sampler: &mut dyn Sampler,
+    ) -> Option<(Ray, Color)> {
         match self.material_type {
-            MaterialType::Lambertian => self.scatter_lambertian(r_in, rec),
-            MaterialType::Metal => self.scatter_metal(r_in, rec),
-            MaterialType::Dielectric => self.scatter_dielectric(r_in, rec),
+            MaterialType::Lambertian => self.scatter_lambertian(r_in, rec, sampler),
+            MaterialType::Metal => self.scatter_metal(r_in, rec, sampler),
+            MaterialType::Dielectric => self.scatter_dielectric(r_in, rec, sampler),
+            MaterialType::DiffuseLight => None,
+            MaterialType::Isotropic => self.scatter_isotropic(r_in, rec, sampler),
+        }
+    }
+
+    pub fn emitted(&self, u: f64, v: f64, p: &Point3) -> Color {
+        match self.material_type {
+            MaterialType::DiffuseLight => self.emit.value(u, v, p),
+            _ => Color::new(0.0, 0.0, 0.0),
         }
     }
 
@@ -111,3 +220,46 @@ impl Material {
         r0 + (1.0 - r0) * (1.0 - coside).powi(5)
     }
 }
+
+/// Refers to a material in a [`Materials`] table.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct MaterialId(u32);
+
+/// The materials of a scene. Objects hold [`MaterialId`]s into the table, so
+/// a hit names its material without copying it.
+#[derive(Clone, Default)]
+pub struct Materials {
+    materials: Vec<Material>,
+}
+
+impl Materials {
+    pub fn new() -> Self {
+        Materials::default()
+    }
+
+    pub fn add(&mut self, material: Material) -> MaterialId {
+        let id = MaterialId(self.materials.len() as u32);
+        self.materials.push(material);
+        id
+    }
+
+    pub fn get(&self, id: MaterialId) -> Option<&Material> {
+        self.materials.get(id.0 as usize)
+    }
+
+    pub fn len(&self) -> usize {
+        self.materials.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.materials.is_empty()
+    }
+}
+
+impl Index<MaterialId> for Materials {
+    type Output = Material;
+
+    fn index(&self, id: MaterialId) -> &Material {
+        &self.materials[id.0 as usize]
+    }
+}
@@ -0,0 +1,77 @@
+use std::sync::Arc;
+
+use crate::{
+    aabb::Aabb, hit_record::HitRecord, hittable::Hittable, interval::Interval,
+    material::MaterialId, ray::Ray, vec3::Vec3,
+};
+
+/// Smoke, fog or any other volume of uniform density filling a closed
+/// `boundary`. A ray inside it scatters after an exponentially distributed
+/// distance, where the `phase` material (normally
+/// [`Material::new_isotropic`](crate::material::Material::new_isotropic))
+/// picks the new direction; rays that make it through see what lies beyond.
+pub struct ConstantMedium {
+    boundary: Arc<dyn Hittable + Send + Sync>,
+    neg_inv_density: f64,
+    phase: MaterialId,
+}
+
+impl ConstantMedium {
+    /// `density` is the chance of scattering per unit of distance travelled.
+    /// Panics if it is not positive.
+    pub fn new(boundary: Arc<dyn Hittable + Send + Sync>, density: f64, phase: MaterialId) -> Self {
+        assert!(density > 0.0, "Medium density must be positive");
+        ConstantMedium {
+            boundary,
+            neg_inv_density: -1.0 / density,
+            phase,
+        }
+    }
+
+    pub fn boundary(&self) -> &Arc<dyn Hittable + Send + Sync> {
+        &self.boundary
+    }
+}
+
+impl Hittable for ConstantMedium {
+    fn hit(&self, r: &Ray, ray_t: &Interval) -> Option<HitRecord> {
+        // `hit` has no sampler, so the distance comes from the sample the
+        // camera drew for this ray: asking again with a narrower `ray_t`
+        // gives the same answer.
+        let mut remaining = self.neg_inv_density * (1.0 - r.medium_sample).ln();
+        let ray_length = r.dir.length();
+
+        // Walk through every stretch of the ray inside the boundary in turn,
+        // so concave boundaries work as well as convex ones.
+        let mut from = f64::NEG_INFINITY;
+        loop {
+            let enter = self.boundary.hit(r, &Interval::new(from, f64::INFINITY))?;
+            let exit = self
+                .boundary
+                .hit(r, &Interval::new(enter.t + 0.0001, f64::INFINITY))?;
+
+            let start = enter.t.max(ray_t.min);
+            let end = exit.t.min(ray_t.max);
+            if start < end {
+                let inside = (end - start) * ray_length;
+                if remaining < inside {
+                    let t = start + remaining / ray_length;
+                    // The normal is arbitrary: the phase function ignores it.
+                    let mut rec = HitRecord::new(r.at(t), Vec3::new(1.0, 0.0, 0.0), t, self.phase);
+                    rec.front_face = true;
+                    return Some(rec);
+                }
+                remaining -= inside;
+            }
+
+            if exit.t >= ray_t.max {
+                return None;
+            }
+            from = exit.t + 0.0001;
+        }
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        self.boundary.bounding_box()
+    }
+}
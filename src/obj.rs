@@ -0,0 +1,427 @@
+use std::{
+    collections::HashMap,
+    fmt, fs,
+    path::{Path, PathBuf},
+    str::SplitWhitespace,
+};
+
+use crate::{
+    hittable_list::HittableList,
+    material::{Material, MaterialId, Materials},
+    triangle_mesh::TriangleMesh,
+    vec3::{Color, Point3, Vec3},
+};
+
+#[derive(Debug)]
+pub enum ObjError {
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    Parse {
+        path: PathBuf,
+        line: usize,
+        message: String,
+    },
+}
+
+impl fmt::Display for ObjError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ObjError::Io { path, source } => write!(f, "{}: {}", path.display(), source),
+            ObjError::Parse {
+                path,
+                line,
+                message,
+            } => write!(f, "{}:{}: {}", path.display(), line, message),
+        }
+    }
+}
+
+impl std::error::Error for ObjError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ObjError::Io { source, .. } => Some(source),
+            ObjError::Parse { .. } => None,
+        }
+    }
+}
+
+/// Material parameters as read from a `.mtl` file.
+#[derive(Clone, Debug)]
+pub struct MtlMaterial {
+    pub kd: Color,
+    pub ks: Color,
+    pub ke: Color,
+    pub ns: f64,
+    pub ni: f64,
+    pub d: f64,
+    pub illum: u32,
+}
+
+impl Default for MtlMaterial {
+    fn default() -> Self {
+        MtlMaterial {
+            kd: Color::new(0.8, 0.8, 0.8),
+            ks: Color::new(0.0, 0.0, 0.0),
+            ke: Color::new(0.0, 0.0, 0.0),
+            ns: 0.0,
+            ni: 1.0,
+            d: 1.0,
+            illum: 2,
+        }
+    }
+}
+
+impl MtlMaterial {
+    /// Maps the Phong-style MTL parameters onto the closest material we have:
+    /// emissive surfaces become lights, transparent surfaces become
+    /// dielectrics, surfaces whose specular reflectance dominates become metals
+    /// (rougher for lower `Ns`), and everything else is lambertian.
+    pub fn to_material(&self) -> Material {
+        if max_component(self.ke) > 0.0 {
+            return Material::new_diffuse_light(self.ke);
+        }
+
+        if self.d < 1.0 || matches!(self.illum, 4 | 6 | 7) {
+            let ir = if self.ni > 1.0 { self.ni } else { 1.5 };
+            return Material::new_dielectric(ir);
+        }
+
+        if self.illum == 3 || max_component(self.ks) > max_component(self.kd) {
+            let fuzz = (2.0 / (self.ns + 2.0)).sqrt().clamp(0.0, 1.0);
+            return Material::new_metal(self.ks, fuzz);
+        }
+
+        Material::new_lambertian(self.kd)
+    }
+}
+
+fn max_component(c: Color) -> f64 {
+    c.x.max(c.y).max(c.z)
+}
+
+struct Statement<'a> {
+    path: &'a Path,
+    line: usize,
+    keyword: &'a str,
+    tokens: SplitWhitespace<'a>,
+}
+
+impl<'a> Statement<'a> {
+    fn error(&self, message: String) -> ObjError {
+        ObjError::Parse {
+            path: self.path.to_path_buf(),
+            line: self.line,
+            message,
+        }
+    }
+
+    fn next_token(&mut self, what: &str) -> Result<&'a str, ObjError> {
+        match self.tokens.next() {
+            Some(token) => Ok(token),
+            None => Err(self.error(format!("`{}` is missing {}", self.keyword, what))),
+        }
+    }
+
+    fn f64(&mut self, what: &str) -> Result<f64, ObjError> {
+        let token = self.next_token(what)?;
+        token.parse::<f64>().map_err(|_| {
+            self.error(format!(
+                "invalid {} `{}` in `{}`",
+                what, token, self.keyword
+            ))
+        })
+    }
+
+    fn optional_f64(&mut self, what: &str) -> Result<Option<f64>, ObjError> {
+        match self.tokens.next() {
+            Some(token) => token.parse::<f64>().map(Some).map_err(|_| {
+                self.error(format!(
+                    "invalid {} `{}` in `{}`",
+                    what, token, self.keyword
+                ))
+            }),
+            None => Ok(None),
+        }
+    }
+
+    fn vec3(&mut self) -> Result<Vec3, ObjError> {
+        Ok(Vec3::new(self.f64("x")?, self.f64("y")?, self.f64("z")?))
+    }
+
+    fn color(&mut self) -> Result<Color, ObjError> {
+        let r = self.f64("red component")?;
+        // A single value is shorthand for a grey.
+        match self.optional_f64("green component")? {
+            Some(g) => Ok(Color::new(r, g, self.f64("blue component")?)),
+            None => Ok(Color::new(r, r, r)),
+        }
+    }
+
+    fn rest(&self) -> String {
+        self.tokens.clone().collect::<Vec<_>>().join(" ")
+    }
+}
+
+fn read_file(path: &Path) -> Result<String, ObjError> {
+    fs::read_to_string(path).map_err(|source| ObjError::Io {
+        path: path.to_path_buf(),
+        source,
+    })
+}
+
+fn statements<'a>(path: &'a Path, text: &'a str) -> impl Iterator<Item = Statement<'a>> {
+    text.lines().enumerate().filter_map(move |(i, line)| {
+        let line = line.split('#').next().unwrap_or("");
+        let mut tokens = line.split_whitespace();
+        let keyword = tokens.next()?;
+        Some(Statement {
+            path,
+            line: i + 1,
+            keyword,
+            tokens,
+        })
+    })
+}
+
+/// Reads every material from a `.mtl` file.
+pub fn load_mtl(path: impl AsRef<Path>) -> Result<HashMap<String, MtlMaterial>, ObjError> {
+    let path = path.as_ref();
+    let text = read_file(path)?;
+
+    let mut materials = HashMap::new();
+    let mut current: Option<(String, MtlMaterial)> = None;
+
+    for mut st in statements(path, &text) {
+        if st.keyword == "newmtl" {
+            let name = st.rest();
+            if name.is_empty() {
+                return Err(st.error("`newmtl` is missing a material name".to_string()));
+            }
+            if let Some((name, material)) = current.take() {
+                materials.insert(name, material);
+            }
+            current = Some((name, MtlMaterial::default()));
+            continue;
+        }
+
+        let Some((_, material)) = current.as_mut() else {
+            return Err(st.error(format!("`{}` before any `newmtl`", st.keyword)));
+        };
+
+        match st.keyword {
+            "Kd" => material.kd = st.color()?,
+            "Ks" => material.ks = st.color()?,
+            "Ke" => material.ke = st.color()?,
+            "Ns" => material.ns = st.f64("exponent")?,
+            "Ni" => material.ni = st.f64("index of refraction")?,
+            "d" => material.d = st.f64("dissolve")?,
+            "Tr" => material.d = 1.0 - st.f64("transparency")?,
+            "illum" => {
+                let token = st.next_token("an illumination model")?;
+                material.illum = token
+                    .parse()
+                    .map_err(|_| st.error(format!("invalid illumination model `{}`", token)))?;
+            }
+            // Ambient color, texture maps and other extensions are not used.
+            _ => {}
+        }
+    }
+
+    if let Some((name, material)) = current {
+        materials.insert(name, material);
+    }
+
+    Ok(materials)
+}
+
+#[derive(Default)]
+struct Group {
+    positions: Vec<Point3>,
+    normals: Vec<Vec3>,
+    uvs: Vec<(f64, f64)>,
+    indices: Vec<[u32; 3]>,
+    vertex_map: HashMap<(usize, Option<usize>, Option<usize>), u32>,
+    has_all_normals: bool,
+    has_all_uvs: bool,
+}
+
+impl Group {
+    fn new() -> Self {
+        Group {
+            has_all_normals: true,
+            has_all_uvs: true,
+            ..Default::default()
+        }
+    }
+
+    fn vertex(
+        &mut self,
+        key: (usize, Option<usize>, Option<usize>),
+        positions: &[Point3],
+        uvs: &[(f64, f64)],
+        normals: &[Vec3],
+    ) -> u32 {
+        if let Some(&index) = self.vertex_map.get(&key) {
+            return index;
+        }
+
+        let (p, vt, vn) = key;
+        let index = self.positions.len() as u32;
+        self.positions.push(positions[p]);
+
+        match vt {
+            Some(vt) => self.uvs.push(uvs[vt]),
+            None => {
+                self.has_all_uvs = false;
+                self.uvs.push((0.0, 0.0));
+            }
+        }
+
+        match vn {
+            Some(vn) => self.normals.push(normals[vn]),
+            None => {
+                self.has_all_normals = false;
+                self.normals.push(Vec3::new(0.0, 0.0, 0.0));
+            }
+        }
+
+        self.vertex_map.insert(key, index);
+        index
+    }
+
+    fn into_mesh(self, material: MaterialId) -> TriangleMesh {
+        let normals = self.has_all_normals.then_some(self.normals);
+        let uvs = self.has_all_uvs.then_some(self.uvs);
+        TriangleMesh::new(self.positions, self.indices, normals, uvs, material)
+    }
+}
+
+/// Resolves a 1-based (or negative, relative) OBJ index into `0..count`.
+fn resolve_index(st: &Statement, token: &str, count: usize, what: &str) -> Result<usize, ObjError> {
+    let index: i64 = token
+        .parse()
+        .map_err(|_| st.error(format!("invalid {} index `{}`", what, token)))?;
+
+    let resolved = match index {
+        0 => None,
+        i if i > 0 => Some(i - 1),
+        i => Some(count as i64 + i),
+    };
+
+    match resolved {
+        Some(i) if i >= 0 && (i as usize) < count => Ok(i as usize),
+        _ => Err(st.error(format!(
+            "{} index {} out of range ({} defined so far)",
+            what, index, count
+        ))),
+    }
+}
+
+/// Loads a Wavefront OBJ file, along with any `.mtl` libraries it references,
+/// as one triangle mesh per material. The materials used are added to
+/// `materials`; faces without a `usemtl` get `default_material`.
+pub fn load_obj(
+    path: impl AsRef<Path>,
+    materials: &mut Materials,
+    default_material: MaterialId,
+) -> Result<HittableList, ObjError> {
+    let path = path.as_ref();
+    let text = read_file(path)?;
+    let base_dir = path.parent().unwrap_or(Path::new(""));
+
+    let mut positions: Vec<Point3> = Vec::new();
+    let mut uvs: Vec<(f64, f64)> = Vec::new();
+    let mut normals: Vec<Vec3> = Vec::new();
+
+    let mut library: HashMap<String, MtlMaterial> = HashMap::new();
+    let mut groups: Vec<(Option<String>, Group)> = vec![(None, Group::new())];
+    let mut current_group = 0;
+
+    for mut st in statements(path, &text) {
+        match st.keyword {
+            "v" => positions.push(st.vec3()?),
+            "vn" => normals.push(st.vec3()?),
+            "vt" => {
+                let u = st.f64("u")?;
+                let v = st.optional_f64("v")?.unwrap_or(0.0);
+                uvs.push((u, v));
+            }
+            "f" => {
+                let mut face: Vec<u32> = Vec::new();
+                for vertex in st.tokens.clone() {
+                    let mut parts = vertex.split('/');
+                    let p =
+                        resolve_index(&st, parts.next().unwrap_or(""), positions.len(), "vertex")?;
+                    let vt = match parts.next() {
+                        Some("") | None => None,
+                        Some(token) => {
+                            Some(resolve_index(&st, token, uvs.len(), "texture coordinate")?)
+                        }
+                    };
+                    let vn = match parts.next() {
+                        Some("") | None => None,
+                        Some(token) => Some(resolve_index(&st, token, normals.len(), "normal")?),
+                    };
+
+                    let group = &mut groups[current_group].1;
+                    face.push(group.vertex((p, vt, vn), &positions, &uvs, &normals));
+                }
+
+                if face.len() < 3 {
+                    return Err(st.error(format!(
+                        "face has {} vertices, expected at least 3",
+                        face.len()
+                    )));
+                }
+
+                // Fan triangulation; fine for the convex polygons OBJ exporters emit.
+                let group = &mut groups[current_group].1;
+                for i in 1..(face.len() - 1) {
+                    group.indices.push([face[0], face[i], face[i + 1]]);
+                }
+            }
+            "mtllib" => {
+                let names = st.rest();
+                if names.is_empty() {
+                    return Err(st.error("`mtllib` is missing a file name".to_string()));
+                }
+                for name in names.split_whitespace() {
+                    library.extend(load_mtl(base_dir.join(name))?);
+                }
+            }
+            "usemtl" => {
+                let name = st.rest();
+                if !library.contains_key(&name) {
+                    return Err(st.error(format!("unknown material `{}`", name)));
+                }
+                current_group = match groups.iter().position(|(n, _)| n.as_ref() == Some(&name)) {
+                    Some(index) => index,
+                    None => {
+                        groups.push((Some(name), Group::new()));
+                        groups.len() - 1
+                    }
+                };
+            }
+            // Object/group names, smoothing groups, lines and points don't
+            // affect what we render.
+            _ => {}
+        }
+    }
+
+    let mut list = HittableList::new();
+    for (name, group) in groups {
+        if group.indices.is_empty() {
+            continue;
+        }
+
+        let material = match name {
+            Some(name) => materials.add(library[&name].to_material()),
+            None => default_material,
+        };
+        list.add(Box::new(group.into_mesh(material)));
+    }
+
+    Ok(list)
+}
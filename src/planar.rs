@@ -0,0 +1,244 @@
+use crate::{
+    aabb::Aabb,
+    hit_record::HitRecord,
+    hittable::Hittable,
+    hittable_list::HittableList,
+    interval::Interval,
+    material::MaterialId,
+    ray::Ray,
+    vec3::{Point3, Vec3},
+};
+
+/// The plane through `q` spanned by the edge vectors `u` and `v`. Every
+/// planar primitive intersects its plane the same way and then decides from
+/// the hit's plane coordinates `(alpha, beta)`, where the hit point is
+/// `q + alpha u + beta v`, whether the ray actually hit the shape.
+#[derive(Clone, Copy, Debug)]
+struct PlaneFrame {
+    q: Point3,
+    u: Vec3,
+    v: Vec3,
+    // n / (n · n) for the unnormalized normal n = u × v, which turns the
+    // cross products below into plane coordinates.
+    w: Vec3,
+    normal: Vec3,
+    d: f64,
+}
+
+impl PlaneFrame {
+    fn new(q: Point3, u: Vec3, v: Vec3) -> Self {
+        let n = u.cross(v);
+        let normal = n.unit_vector();
+        PlaneFrame {
+            q,
+            u,
+            v,
+            w: n / n.dot(n),
+            normal,
+            d: normal.dot(q),
+        }
+    }
+
+    /// Where `r` crosses the plane inside `ray_t`, as `(t, alpha, beta)`.
+    /// Rays parallel to the plane never hit it.
+    fn intersect(&self, r: &Ray, ray_t: &Interval) -> Option<(f64, f64, f64)> {
+        let denom = self.normal.dot(r.dir);
+        if denom.abs() < 1e-8 {
+            return None;
+        }
+
+        let t = (self.d - self.normal.dot(r.orig)) / denom;
+        if !ray_t.surrounds(t) {
+            return None;
+        }
+
+        let planar = r.at(t) - self.q;
+        let alpha = self.w.dot(planar.cross(self.v));
+        let beta = self.w.dot(self.u.cross(planar));
+        Some((t, alpha, beta))
+    }
+
+    fn hit_record(&self, t: f64, r: &Ray, material: MaterialId, u: f64, v: f64) -> HitRecord {
+        let mut rec = HitRecord::build(t, r, &self.normal, material);
+        (rec.u, rec.v) = (u, v);
+        rec
+    }
+}
+
+/// A parallelogram with one corner at `q` and edges `u` and `v`. The texture
+/// coordinates run from 0 to 1 along each edge. Seen from the side where
+/// `u` turns counter-clockwise into `v`, the quad faces the viewer.
+#[derive(Clone, Copy, Debug)]
+pub struct Quad {
+    frame: PlaneFrame,
+    material: MaterialId,
+    bbox: Aabb,
+}
+
+impl Quad {
+    pub fn new(q: Point3, u: Vec3, v: Vec3, material: MaterialId) -> Self {
+        let bbox = Aabb::surrounding(
+            &Aabb::from_points(q, q + u + v),
+            &Aabb::from_points(q + u, q + v),
+        );
+        Quad {
+            frame: PlaneFrame::new(q, u, v),
+            material,
+            bbox,
+        }
+    }
+}
+
+impl Hittable for Quad {
+    fn hit(&self, r: &Ray, ray_t: &Interval) -> Option<HitRecord> {
+        let (t, alpha, beta) = self.frame.intersect(r, ray_t)?;
+        let unit = Interval::new(0.0, 1.0);
+        if !unit.contains(alpha) || !unit.contains(beta) {
+            return None;
+        }
+
+        Some(self.frame.hit_record(t, r, self.material, alpha, beta))
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        self.bbox
+    }
+}
+
+/// A single triangle. The texture coordinates are the barycentric weights
+/// of `b` and `c`. Facing works as for [`Quad`], with `b - a` and `c - a` as
+/// the edges.
+#[derive(Clone, Copy, Debug)]
+pub struct Triangle {
+    frame: PlaneFrame,
+    material: MaterialId,
+    bbox: Aabb,
+}
+
+impl Triangle {
+    pub fn new(a: Point3, b: Point3, c: Point3, material: MaterialId) -> Self {
+        Triangle {
+            frame: PlaneFrame::new(a, b - a, c - a),
+            material,
+            bbox: Aabb::surrounding(&Aabb::from_points(a, b), &Aabb::from_points(a, c)),
+        }
+    }
+}
+
+impl Hittable for Triangle {
+    fn hit(&self, r: &Ray, ray_t: &Interval) -> Option<HitRecord> {
+        let (t, alpha, beta) = self.frame.intersect(r, ray_t)?;
+        if alpha < 0.0 || beta < 0.0 || alpha + beta > 1.0 {
+            return None;
+        }
+
+        Some(self.frame.hit_record(t, r, self.material, alpha, beta))
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        self.bbox
+    }
+}
+
+/// A flat disk facing along `normal`. The texture coordinates map the
+/// disk's square bounds in its plane onto [0, 1].
+#[derive(Clone, Copy, Debug)]
+pub struct Disk {
+    frame: PlaneFrame,
+    material: MaterialId,
+    bbox: Aabb,
+}
+
+impl Disk {
+    pub fn new(center: Point3, normal: Vec3, radius: f64, material: MaterialId) -> Self {
+        let (u, v) = normal.orthonormal_basis();
+        let frame = PlaneFrame::new(center, radius * u, radius * v);
+
+        // How far the rim reaches along each axis.
+        let n = frame.normal;
+        let reach = Vec3::new(
+            (1.0 - n.x * n.x).max(0.0).sqrt(),
+            (1.0 - n.y * n.y).max(0.0).sqrt(),
+            (1.0 - n.z * n.z).max(0.0).sqrt(),
+        ) * radius;
+
+        Disk {
+            frame,
+            material,
+            bbox: Aabb::from_points(center - reach, center + reach),
+        }
+    }
+}
+
+impl Hittable for Disk {
+    fn hit(&self, r: &Ray, ray_t: &Interval) -> Option<HitRecord> {
+        let (t, alpha, beta) = self.frame.intersect(r, ray_t)?;
+        if alpha * alpha + beta * beta > 1.0 {
+            return None;
+        }
+
+        let (u, v) = (0.5 * (alpha + 1.0), 0.5 * (beta + 1.0));
+        Some(self.frame.hit_record(t, r, self.material, u, v))
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        self.bbox
+    }
+}
+
+/// An infinite plane through `point` facing along `normal`. The texture
+/// coordinates are distances along two directions in the plane, so image
+/// textures repeat once per unit. Its bounding box is unbounded; a
+/// [`Bvh`](crate::bvh::Bvh) keeps such objects out of the tree.
+#[derive(Clone, Copy, Debug)]
+pub struct Plane {
+    frame: PlaneFrame,
+    material: MaterialId,
+}
+
+impl Plane {
+    pub fn new(point: Point3, normal: Vec3, material: MaterialId) -> Self {
+        let (u, v) = normal.orthonormal_basis();
+        Plane {
+            frame: PlaneFrame::new(point, u, v),
+            material,
+        }
+    }
+}
+
+impl Hittable for Plane {
+    fn hit(&self, r: &Ray, ray_t: &Interval) -> Option<HitRecord> {
+        let (t, alpha, beta) = self.frame.intersect(r, ray_t)?;
+        Some(self.frame.hit_record(t, r, self.material, alpha, beta))
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        Aabb::UNIVERSE
+    }
+}
+
+/// The axis-aligned box with opposite corners `a` and `b`, as six outward
+/// facing quads.
+pub fn make_box(a: Point3, b: Point3, material: MaterialId) -> HittableList {
+    let min = Point3::new(a.x.min(b.x), a.y.min(b.y), a.z.min(b.z));
+    let max = Point3::new(a.x.max(b.x), a.y.max(b.y), a.z.max(b.z));
+
+    let dx = Vec3::new(max.x - min.x, 0.0, 0.0);
+    let dy = Vec3::new(0.0, max.y - min.y, 0.0);
+    let dz = Vec3::new(0.0, 0.0, max.z - min.z);
+
+    let mut sides = HittableList::new();
+    let faces = [
+        (Point3::new(min.x, min.y, max.z), dx, dy),  // front
+        (Point3::new(max.x, min.y, max.z), -dz, dy), // right
+        (Point3::new(max.x, min.y, min.z), -dx, dy), // back
+        (Point3::new(min.x, min.y, min.z), dz, dy),  // left
+        (Point3::new(min.x, max.y, max.z), dx, -dz), // top
+        (Point3::new(min.x, min.y, min.z), dx, dz),  // bottom
+    ];
+    for (q, u, v) in faces {
+        sides.add(Box::new(Quad::new(q, u, v, material)));
+    }
+
+    sides
+}
@@ -0,0 +1,373 @@
+use std::{f64::consts::PI, ops::Deref};
+
+/// Coefficients this close to zero are treated as zero when deciding which
+/// case of a closed-form solution applies.
+const EPSILON: f64 = 1e-12;
+
+/// The real roots of a polynomial of degree four or less, in ascending
+/// order. Dereferences to a slice.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Roots {
+    values: [f64; 4],
+    len: usize,
+}
+
+impl Roots {
+    fn push(&mut self, x: f64) {
+        self.values[self.len] = x;
+        self.len += 1;
+    }
+
+    fn sorted(mut self) -> Self {
+        self.values[..self.len].sort_by(f64::total_cmp);
+        self
+    }
+}
+
+impl Deref for Roots {
+    type Target = [f64];
+
+    fn deref(&self) -> &[f64] {
+        &self.values[..self.len]
+    }
+}
+
+/// Real roots of `a x² + b x + c`. Falls back to the linear case when `a`
+/// is zero.
+pub fn solve_quadratic(a: f64, b: f64, c: f64) -> Roots {
+    let mut roots = Roots::default();
+    if a == 0.0 {
+        if b != 0.0 {
+            roots.push(-c / b);
+        }
+        return roots;
+    }
+
+    let discriminant = b * b - 4.0 * a * c;
+    if discriminant < 0.0 {
+        return roots;
+    }
+    if discriminant == 0.0 {
+        roots.push(-0.5 * b / a);
+        return roots;
+    }
+
+    // Avoids cancellation between `b` and the square root.
+    let q = -0.5 * (b + discriminant.sqrt().copysign(b));
+    roots.push(q / a);
+    roots.push(c / q);
+    roots.sorted()
+}
+
+/// Real roots of `a x³ + b x² + c x + d`, by Cardano's formula or, with
+/// three real roots, the trigonometric method.
+pub fn solve_cubic(a: f64, b: f64, c: f64, d: f64) -> Roots {
+    if a == 0.0 {
+        return solve_quadratic(b, c, d);
+    }
+
+    let (b, c, d) = (b / a, c / a, d / a);
+
+    // Substituting x = y - b/3 gives y³ + p y + q.
+    let shift = b / 3.0;
+    let p = c - b * shift;
+    let q = 2.0 * shift * shift * shift - shift * c + d;
+
+    let mut roots = Roots::default();
+    let half_q = 0.5 * q;
+    let third_p = p / 3.0;
+    let discriminant = half_q * half_q + third_p * third_p * third_p;
+
+    if p.abs() < EPSILON && q.abs() < EPSILON {
+        roots.push(-shift);
+    } else if discriminant > 0.0 {
+        let sqrt_d = discriminant.sqrt();
+        roots.push((-half_q + sqrt_d).cbrt() + (-half_q - sqrt_d).cbrt() - shift);
+    } else {
+        // Three real roots (some possibly repeated); p < 0 here.
+        let m = 2.0 * (-third_p).sqrt();
+        let theta = (3.0 * q / (p * m)).clamp(-1.0, 1.0).acos() / 3.0;
+        for k in 0..3 {
+            roots.push(m * (theta - 2.0 * PI * k as f64 / 3.0).cos() - shift);
+        }
+    }
+
+    for root in roots.values[..roots.len].iter_mut() {
+        *root = polish(&[1.0, b, c, d], *root);
+    }
+    roots.sorted()
+}
+
+/// Real roots of `a x⁴ + b x³ + c x² + d x + e`, by Ferrari's method with
+/// each root polished by Newton's method on the original polynomial.
+///
+/// ```
+/// use ray_tracing_in_a_weekend_rust::polynomial::solve_quartic;
+///
+/// // (x - 1)(x - 2)(x - 3)(x - 4)
+/// let roots = solve_quartic(1.0, -10.0, 35.0, -50.0, 24.0);
+/// assert_eq!(roots.len(), 4);
+/// for (root, expected) in roots.iter().zip([1.0, 2.0, 3.0, 4.0]) {
+///     assert!((root - expected).abs() < 1e-9);
+/// }
+/// ```
+pub fn solve_quartic(a: f64, b: f64, c: f64, d: f64, e: f64) -> Roots {
+    if a == 0.0 {
+        return solve_cubic(b, c, d, e);
+    }
+
+    let (b, c, d, e) = (b / a, c / a, d / a, e / a);
+
+    // Substituting x = y - b/4 gives y⁴ + p y² + q y + r.
+    let shift = 0.25 * b;
+    let shift_sq = shift * shift;
+    let p = c - 6.0 * shift_sq;
+    let q = d - 2.0 * c * shift + 8.0 * shift * shift_sq;
+    let r = e - d * shift + c * shift_sq - 3.0 * shift_sq * shift_sq;
+
+    let mut depressed = Roots::default();
+    if r.abs() < EPSILON {
+        // y (y³ + p y + q) = 0
+        depressed.push(0.0);
+        for &y in solve_cubic(1.0, 0.0, p, q).iter() {
+            depressed.push(y);
+        }
+    } else if q.abs() < EPSILON {
+        // Biquadratic: a quadratic in y².
+        for &z in solve_quadratic(1.0, p, r).iter() {
+            if z >= 0.0 {
+                depressed.push(-z.sqrt());
+                depressed.push(z.sqrt());
+            }
+        }
+    } else {
+        // Any real root of the resolvent cubic splits the quartic into two
+        // quadratics; the largest keeps both square roots real.
+        let resolvent = solve_cubic(1.0, -0.5 * p, -r, 0.5 * r * p - 0.125 * q * q);
+        let Some(&z) = resolvent.last() else {
+            return Roots::default();
+        };
+
+        let u = z * z - r;
+        let v = 2.0 * z - p;
+        if u < -EPSILON || v < -EPSILON {
+            return Roots::default();
+        }
+        let (u, v) = (u.max(0.0).sqrt(), v.max(0.0).sqrt().copysign(q));
+
+        for &y in solve_quadratic(1.0, v, z - u).iter() {
+            depressed.push(y);
+        }
+        for &y in solve_quadratic(1.0, -v, z + u).iter() {
+            depressed.push(y);
+        }
+    }
+
+    let mut roots = Roots::default();
+    for &y in depressed.iter() {
+        roots.push(polish(&[1.0, b, c, d, e], y - shift));
+    }
+    roots.sorted()
+}
+
+/// A couple of Newton steps on the polynomial with `coefficients` (highest
+/// degree first), undone if they make the residual worse.
+fn polish(coefficients: &[f64], mut x: f64) -> f64 {
+    let eval = |x: f64| {
+        coefficients.iter().fold((0.0, 0.0), |(value, slope), &k| {
+            (value * x + k, slope * x + value)
+        })
+    };
+
+    for _ in 0..2 {
+        let (value, slope) = eval(x);
+        if slope == 0.0 {
+            break;
+        }
+        let next = x - value / slope;
+        if !next.is_finite() || eval(next).0.abs() > value.abs() {
+            break;
+        }
+        x = next;
+    }
+    x
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Checks that every expected root was found and nothing else was.
+    /// Repeated roots may be reported once or several times.
+    fn assert_roots(roots: &[f64], expected: &[f64], tolerance: f64) {
+        for &x in expected {
+            assert!(
+                roots.iter().any(|r| (r - x).abs() < tolerance),
+                "missing root {} in {:?}",
+                x,
+                roots
+            );
+        }
+        for &r in roots {
+            assert!(
+                expected.iter().any(|x| (r - x).abs() < tolerance),
+                "unexpected root {} (expected {:?})",
+                r,
+                expected
+            );
+        }
+        assert!(
+            roots.windows(2).all(|w| w[0] <= w[1]),
+            "unsorted {:?}",
+            roots
+        );
+    }
+
+    /// Coefficients of `scale (x - r0)(x - r1)...`, highest degree first.
+    fn expand(scale: f64, roots: &[f64]) -> Vec<f64> {
+        let mut coefficients = vec![scale];
+        for &r in roots {
+            let mut next = coefficients.clone();
+            next.push(0.0);
+            for (i, &k) in coefficients.iter().enumerate() {
+                next[i + 1] -= r * k;
+            }
+            coefficients = next;
+        }
+        coefficients
+    }
+
+    fn quartic(scale: f64, roots: [f64; 4]) -> Roots {
+        let k = expand(scale, &roots);
+        solve_quartic(k[0], k[1], k[2], k[3], k[4])
+    }
+
+    #[test]
+    fn quadratic_cases() {
+        assert_roots(&solve_quadratic(1.0, -3.0, 2.0), &[1.0, 2.0], 1e-12);
+        assert_roots(&solve_quadratic(1.0, -2.0, 1.0), &[1.0], 1e-12);
+        assert_roots(&solve_quadratic(1.0, 0.0, 1.0), &[], 0.0);
+        assert_roots(&solve_quadratic(0.0, 2.0, -1.0), &[0.5], 1e-12);
+        // Cancellation-prone: roots 1e-8 and 1e8.
+        assert_roots(&solve_quadratic(1.0, -1e8, 1.0), &[1e-8, 1e8], 1e-15 * 1e8);
+    }
+
+    #[test]
+    fn cubic_cases() {
+        let k = expand(2.0, &[-1.0, 0.5, 3.0]);
+        assert_roots(
+            &solve_cubic(k[0], k[1], k[2], k[3]),
+            &[-1.0, 0.5, 3.0],
+            1e-9,
+        );
+        let k = expand(1.0, &[2.0, 2.0, 2.0]);
+        assert_roots(&solve_cubic(k[0], k[1], k[2], k[3]), &[2.0], 1e-9);
+        let k = expand(1.0, &[1.0, 1.0, -2.0]);
+        assert_roots(&solve_cubic(k[0], k[1], k[2], k[3]), &[1.0, -2.0], 1e-6);
+        // x³ + x + 1 has a single real root.
+        let roots = solve_cubic(1.0, 0.0, 1.0, 1.0);
+        assert_eq!(roots.len(), 1);
+        assert!((roots[0].powi(3) + roots[0] + 1.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn quartic_distinct_roots() {
+        assert_roots(
+            &quartic(1.0, [-3.0, -0.5, 0.25, 7.0]),
+            &[-3.0, -0.5, 0.25, 7.0],
+            1e-9,
+        );
+        assert_roots(
+            &quartic(-4.0, [-2.0, 1.0, 1.5, 10.0]),
+            &[-2.0, 1.0, 1.5, 10.0],
+            1e-9,
+        );
+    }
+
+    #[test]
+    fn quartic_repeated_roots() {
+        // Double roots are only accurate to about the square root of the
+        // rounding error.
+        assert_roots(&quartic(1.0, [1.0, 1.0, 2.0, 2.0]), &[1.0, 2.0], 1e-6);
+        assert_roots(
+            &quartic(1.0, [-1.0, 0.5, 0.5, 3.0]),
+            &[-1.0, 0.5, 3.0],
+            1e-6,
+        );
+        assert_roots(&quartic(1.0, [2.0, 2.0, 2.0, -1.0]), &[2.0, -1.0], 1e-4);
+        assert_roots(&quartic(1.0, [1.0, 1.0, 1.0, 1.0]), &[1.0], 1e-4);
+    }
+
+    #[test]
+    fn quartic_near_tangent_roots() {
+        // (x - 1)² - 1e-10 just crosses zero at 1 ± 1e-5: a ray grazing a
+        // surface must still find both hits.
+        let tangent = [1.0, -2.0, 1.0 - 1e-10];
+        let k = [
+            tangent[0],
+            tangent[1] - 7.0 * tangent[0],
+            tangent[2] - 7.0 * tangent[1] + 12.0 * tangent[0],
+            -7.0 * tangent[2] + 12.0 * tangent[1],
+            12.0 * tangent[2],
+        ];
+        let roots = solve_quartic(k[0], k[1], k[2], k[3], k[4]);
+        assert_eq!(roots.len(), 4);
+        assert_roots(&roots, &[1.0 - 1e-5, 1.0 + 1e-5, 3.0, 4.0], 1e-9);
+
+        // (x - 1)² + 1e-10 just misses, leaving only the far pair.
+        let roots = solve_quartic(k[0], k[1], k[2] + 2e-10, k[3] - 7e-10, k[4] + 24e-10);
+        assert_roots(&roots, &[3.0, 4.0], 1e-8);
+    }
+
+    #[test]
+    fn quartic_zero_constant_term_branch() {
+        // Roots sum to zero, so there is no shift and r = 0 exactly.
+        assert_roots(
+            &quartic(1.0, [-3.0, 0.0, 1.0, 2.0]),
+            &[-3.0, 0.0, 1.0, 2.0],
+            1e-9,
+        );
+        assert_roots(&solve_quartic(1.0, 0.0, 0.0, 0.0, 0.0), &[0.0], 1e-9);
+    }
+
+    #[test]
+    fn quartic_biquadratic_branch() {
+        assert_roots(
+            &solve_quartic(1.0, 0.0, -5.0, 0.0, 4.0),
+            &[-2.0, -1.0, 1.0, 2.0],
+            1e-9,
+        );
+        // Symmetric about 1, so q = 0 after the shift.
+        assert_roots(
+            &quartic(1.0, [-1.0, 0.0, 2.0, 3.0]),
+            &[-1.0, 0.0, 2.0, 3.0],
+            1e-9,
+        );
+        // y⁴ - 4y² + 4 = (y² - 2)²: a double pair at ±√2.
+        let s = 2f64.sqrt();
+        assert_roots(&solve_quartic(1.0, 0.0, -4.0, 0.0, 4.0), &[-s, s], 1e-6);
+        // Only one z is non-negative.
+        assert_roots(
+            &solve_quartic(1.0, 0.0, -3.0, 0.0, -4.0),
+            &[-2.0, 2.0],
+            1e-9,
+        );
+    }
+
+    #[test]
+    fn quartic_without_real_roots() {
+        assert!(solve_quartic(1.0, 0.0, 0.0, 0.0, 1.0).is_empty());
+        assert!(solve_quartic(1.0, 0.0, 5.0, 0.0, 4.0).is_empty());
+        assert!(solve_quartic(2.0, -4.0, 7.0, -5.0, 3.0).is_empty());
+    }
+
+    #[test]
+    fn quartic_falls_back_to_cubic() {
+        let k = expand(1.0, &[-1.0, 2.0, 5.0]);
+        assert_roots(
+            &solve_quartic(0.0, k[0], k[1], k[2], k[3]),
+            &[-1.0, 2.0, 5.0],
+            1e-9,
+        );
+    }
+}
@@ -0,0 +1,132 @@
+use std::{
+    sync::{
+        atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+/// A snapshot of a render's progress, passed to the callback set with
+/// [`Camera::set_progress_callback`](crate::Camera::set_progress_callback).
+#[derive(Clone, Copy, Debug)]
+pub struct Progress {
+    /// The pass being rendered, from 1. Non-progressive renders of uniform
+    /// sampling take a single pass.
+    pub pass: u32,
+    /// Tiles of the current pass finished so far.
+    pub tiles_done: usize,
+    /// Tiles with pixels to sample in the current pass. Adaptive passes skip
+    /// tiles whose pixels have all converged.
+    pub tiles_total: usize,
+    /// Samples in the film so far, including any from a resumed checkpoint.
+    pub samples: u64,
+    /// The render's sample budget. Adaptive renders may finish below it.
+    pub total_samples: u64,
+    /// Samples the film held when the render started.
+    pub resumed_samples: u64,
+    /// Rays traced so far by this render, counting every bounce.
+    pub rays: u64,
+    pub elapsed: Duration,
+}
+
+impl Progress {
+    /// Fraction of the sample budget done, in [0, 1].
+    pub fn fraction(&self) -> f64 {
+        if self.total_samples == 0 {
+            return 1.0;
+        }
+        (self.samples as f64 / self.total_samples as f64).min(1.0)
+    }
+
+    /// Estimated time left, extrapolated from the sampling rate so far.
+    pub fn eta(&self) -> Option<Duration> {
+        let done = self.samples.checked_sub(self.resumed_samples)?;
+        if done == 0 {
+            return None;
+        }
+        let left = self.total_samples.saturating_sub(self.samples);
+        Some(self.elapsed.mul_f64(left as f64 / done as f64))
+    }
+}
+
+/// Called with the render's progress. Calls come from the worker threads,
+/// possibly concurrently.
+pub type ProgressCallback = dyn Fn(&Progress) + Send + Sync;
+
+/// A shared flag for stopping a render from another thread. Clones share the
+/// flag.
+#[derive(Clone, Debug, Default)]
+pub struct CancelToken(Arc<AtomicBool>);
+
+impl CancelToken {
+    pub fn new() -> Self {
+        CancelToken::default()
+    }
+
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// Counters the worker threads update as they finish work.
+pub(crate) struct ProgressTracker<'a> {
+    callback: Option<&'a ProgressCallback>,
+    start: Instant,
+    pass: u32,
+    tiles_done: AtomicUsize,
+    tiles_total: usize,
+    samples: AtomicU64,
+    rays: AtomicU64,
+    total_samples: u64,
+    resumed_samples: u64,
+}
+
+impl<'a> ProgressTracker<'a> {
+    pub(crate) fn new(
+        callback: Option<&'a ProgressCallback>,
+        resumed_samples: u64,
+        total_samples: u64,
+    ) -> Self {
+        ProgressTracker {
+            callback,
+            start: Instant::now(),
+            pass: 0,
+            tiles_done: AtomicUsize::new(0),
+            tiles_total: 0,
+            samples: AtomicU64::new(resumed_samples),
+            rays: AtomicU64::new(0),
+            total_samples,
+            resumed_samples,
+        }
+    }
+
+    pub(crate) fn start_pass(&mut self, pass: u32, tiles: usize) {
+        self.pass = pass;
+        self.tiles_done = AtomicUsize::new(0);
+        self.tiles_total = tiles;
+    }
+
+    /// Counts a finished tile's work and reports it.
+    pub(crate) fn record(&self, samples: u64, rays: u64) {
+        let tiles_done = self.tiles_done.fetch_add(1, Ordering::Relaxed) + 1;
+        let samples = self.samples.fetch_add(samples, Ordering::Relaxed) + samples;
+        let rays = self.rays.fetch_add(rays, Ordering::Relaxed) + rays;
+
+        if let Some(callback) = self.callback {
+            callback(&Progress {
+                pass: self.pass,
+                tiles_done,
+                tiles_total: self.tiles_total,
+                samples,
+                total_samples: self.total_samples,
+                resumed_samples: self.resumed_samples,
+                rays,
+                elapsed: self.start.elapsed(),
+            });
+        }
+    }
+}
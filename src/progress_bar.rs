@@ -0,0 +1,114 @@
+use std::{
+    io::Write,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use ray_tracing_in_a_weekend_rust::Progress;
+
+const WIDTH: usize = 30;
+
+/// Redraws faster than this are skipped; workers report far more often.
+const REDRAW_INTERVAL: Duration = Duration::from_millis(100);
+
+/// A one-line progress bar on stderr, redrawn in place.
+pub struct ProgressBar {
+    state: Mutex<State>,
+}
+
+struct State {
+    last_draw: Option<Instant>,
+    latest: Option<Progress>,
+}
+
+impl ProgressBar {
+    pub fn new() -> Self {
+        ProgressBar {
+            state: Mutex::new(State {
+                last_draw: None,
+                latest: None,
+            }),
+        }
+    }
+
+    pub fn update(&self, progress: &Progress) {
+        let Ok(mut state) = self.state.lock() else {
+            return;
+        };
+        // Workers may report out of order; never move the bar backwards.
+        if state.latest.is_some_and(|p| p.samples > progress.samples) {
+            return;
+        }
+        state.latest = Some(*progress);
+
+        if state
+            .last_draw
+            .is_some_and(|t| t.elapsed() < REDRAW_INTERVAL)
+        {
+            return;
+        }
+        state.last_draw = Some(Instant::now());
+        draw(progress);
+    }
+
+    /// Draws the final state and ends the line.
+    pub fn finish(&self) {
+        let Ok(state) = self.state.lock() else {
+            return;
+        };
+        if let Some(progress) = &state.latest {
+            draw(progress);
+            eprintln!();
+        }
+    }
+}
+
+fn draw(progress: &Progress) {
+    let fraction = progress.fraction();
+    let filled = (fraction * WIDTH as f64).round() as usize;
+    let eta = progress
+        .eta()
+        .map_or_else(|| "--:--".to_string(), format_duration);
+    let seconds = progress.elapsed.as_secs_f64();
+    let rate = if seconds > 0.0 {
+        progress.rays as f64 / seconds
+    } else {
+        0.0
+    };
+
+    let mut stderr = std::io::stderr().lock();
+    // Trailing spaces clear what is left of a longer previous line.
+    let _ = write!(
+        stderr,
+        "\r[{}{}] {:3.0}%  pass {}  tile {}/{}  {} elapsed  {} left  {} rays/s   ",
+        "#".repeat(filled),
+        " ".repeat(WIDTH - filled),
+        fraction * 100.0,
+        progress.pass,
+        progress.tiles_done,
+        progress.tiles_total,
+        format_duration(progress.elapsed),
+        eta,
+        format_count(rate),
+    );
+    let _ = stderr.flush();
+}
+
+fn format_duration(duration: Duration) -> String {
+    let seconds = duration.as_secs();
+    let (hours, minutes, seconds) = (seconds / 3600, seconds / 60 % 60, seconds % 60);
+    if hours > 0 {
+        format!("{}:{:02}:{:02}", hours, minutes, seconds)
+    } else {
+        format!("{}:{:02}", minutes, seconds)
+    }
+}
+
+fn format_count(n: f64) -> String {
+    match n {
+        n if n >= 1e9 => format!("{:.1}G", n / 1e9),
+        n if n >= 1e6 => format!("{:.1}M", n / 1e6),
+        n if n >= 1e3 => format!("{:.1}k", n / 1e3),
+        n => format!("{:.0}", n),
+    }
+}
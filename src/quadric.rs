@@ -0,0 +1,566 @@
+use std::f64::consts::PI;
+
+use crate::{
+    aabb::Aabb,
+    hit_record::HitRecord,
+    hittable::Hittable,
+    interval::Interval,
+    material::MaterialId,
+    polynomial::{solve_quadratic, solve_quartic},
+    ray::Ray,
+    vec3::{Point3, Vec3},
+};
+
+/// An orthonormal coordinate system with `w` along a shape's axis. Shapes
+/// intersect rays in these local coordinates, where they are symmetric
+/// around the z axis. Distances are unchanged, so `t` is the same in both.
+#[derive(Clone, Copy, Debug)]
+struct Frame {
+    origin: Point3,
+    u: Vec3,
+    v: Vec3,
+    w: Vec3,
+}
+
+impl Frame {
+    fn new(origin: Point3, axis: Vec3) -> Self {
+        let (u, v) = axis.orthonormal_basis();
+        Frame {
+            origin,
+            u,
+            v,
+            w: axis.unit_vector(),
+        }
+    }
+
+    fn local(&self, a: Vec3) -> Vec3 {
+        Vec3::new(a.dot(self.u), a.dot(self.v), a.dot(self.w))
+    }
+
+    fn local_point(&self, p: Point3) -> Vec3 {
+        self.local(p - self.origin)
+    }
+
+    fn world(&self, a: Vec3) -> Vec3 {
+        a.x * self.u + a.y * self.v + a.z * self.w
+    }
+
+    /// World bounds of the local box from `min` to `max`.
+    fn bounding_box(&self, min: Vec3, max: Vec3) -> Aabb {
+        let mut lo = Vec3::new(f64::INFINITY, f64::INFINITY, f64::INFINITY);
+        let mut hi = -lo;
+        for corner in 0..8 {
+            let local = Vec3::new(
+                if corner & 1 == 0 { min.x } else { max.x },
+                if corner & 2 == 0 { min.y } else { max.y },
+                if corner & 4 == 0 { min.z } else { max.z },
+            );
+            let p = self.origin + self.world(local);
+            lo = Vec3::new(lo.x.min(p.x), lo.y.min(p.y), lo.z.min(p.z));
+            hi = Vec3::new(hi.x.max(p.x), hi.y.max(p.y), hi.z.max(p.z));
+        }
+        Aabb::from_points(lo, hi)
+    }
+}
+
+/// Position of `p` around the local z axis, from 0 to 1.
+fn azimuth(p: Vec3) -> f64 {
+    (p.y.atan2(p.x) + PI) / (2.0 * PI)
+}
+
+/// What every surface of revolution shares: it spans local z from 0 to
+/// `height`, optionally closed by flat caps at either end.
+#[derive(Clone, Copy, Debug)]
+struct Axis {
+    frame: Frame,
+    height: f64,
+    capped: bool,
+    material: MaterialId,
+    bbox: Aabb,
+}
+
+impl Axis {
+    fn new(base: Point3, top: Point3, radius: f64, capped: bool, material: MaterialId) -> Self {
+        let frame = Frame::new(base, top - base);
+        let height = (top - base).length();
+        Axis {
+            frame,
+            height,
+            capped,
+            material,
+            bbox: frame.bounding_box(
+                Vec3::new(-radius, -radius, 0.0),
+                Vec3::new(radius, radius, height),
+            ),
+        }
+    }
+}
+
+/// The side of a surface of revolution around the local z axis, given as
+/// the zero set of a quadratic.
+trait Profile {
+    fn axis(&self) -> &Axis;
+
+    /// Coefficients `(a, b, c)` of `a t² + b t + c`, which is zero where the
+    /// local ray `o + t d` crosses the side.
+    fn coefficients(&self, o: Vec3, d: Vec3) -> (f64, f64, f64);
+
+    /// A vector along the outward normal of the side at local point `p`.
+    fn gradient(&self, p: Vec3) -> Vec3;
+
+    /// Distance of the side from the axis at local height `z`.
+    fn radius_at(&self, z: f64) -> f64;
+}
+
+enum Part {
+    Side,
+    Bottom,
+    Top,
+}
+
+fn hit_profile(shape: &impl Profile, r: &Ray, ray_t: &Interval) -> Option<HitRecord> {
+    let axis = shape.axis();
+    let o = axis.frame.local_point(r.orig);
+    let d = axis.frame.local(r.dir);
+    let height = Interval::new(0.0, axis.height);
+
+    let mut nearest = None;
+    let (a, b, c) = shape.coefficients(o, d);
+    for &t in solve_quadratic(a, b, c).iter() {
+        if ray_t.surrounds(t) && height.contains(o.z + t * d.z) {
+            nearest = Some((t, Part::Side));
+            break;
+        }
+    }
+
+    if axis.capped && d.z != 0.0 {
+        for (z, part) in [(0.0, Part::Bottom), (axis.height, Part::Top)] {
+            let radius = shape.radius_at(z);
+            let t = (z - o.z) / d.z;
+            if radius <= 0.0 || !ray_t.surrounds(t) || nearest.as_ref().is_some_and(|n| n.0 <= t) {
+                continue;
+            }
+            let p = o + t * d;
+            if p.x * p.x + p.y * p.y <= radius * radius {
+                nearest = Some((t, part));
+            }
+        }
+    }
+
+    let (t, part) = nearest?;
+    let p = o + t * d;
+    // Caps map like a disk.
+    let cap_uv = |z: f64| {
+        let radius = shape.radius_at(z);
+        (0.5 * (p.x / radius + 1.0), 0.5 * (p.y / radius + 1.0))
+    };
+    let (normal, (u, v)) = match part {
+        Part::Side => (shape.gradient(p), (azimuth(p), p.z / axis.height)),
+        Part::Bottom => (Vec3::new(0.0, 0.0, -1.0), cap_uv(0.0)),
+        Part::Top => (Vec3::new(0.0, 0.0, 1.0), cap_uv(axis.height)),
+    };
+
+    let outward_normal = axis.frame.world(normal).unit_vector();
+    let mut rec = HitRecord::build(t, r, &outward_normal, axis.material);
+    (rec.u, rec.v) = (u, v);
+    Some(rec)
+}
+
+/// A cylinder of `radius` around the segment from `base` to `top`, closed
+/// by flat caps when `capped`. The texture coordinates run around the axis
+/// and from base to top; caps map like a [`Disk`](crate::planar::Disk).
+#[derive(Clone, Copy, Debug)]
+pub struct Cylinder {
+    axis: Axis,
+    radius: f64,
+}
+
+impl Cylinder {
+    pub fn new(base: Point3, top: Point3, radius: f64, capped: bool, material: MaterialId) -> Self {
+        Cylinder {
+            axis: Axis::new(base, top, radius, capped, material),
+            radius,
+        }
+    }
+}
+
+impl Profile for Cylinder {
+    fn axis(&self) -> &Axis {
+        &self.axis
+    }
+
+    fn coefficients(&self, o: Vec3, d: Vec3) -> (f64, f64, f64) {
+        (
+            d.x * d.x + d.y * d.y,
+            2.0 * (o.x * d.x + o.y * d.y),
+            o.x * o.x + o.y * o.y - self.radius * self.radius,
+        )
+    }
+
+    fn gradient(&self, p: Vec3) -> Vec3 {
+        Vec3::new(p.x, p.y, 0.0)
+    }
+
+    fn radius_at(&self, _z: f64) -> f64 {
+        self.radius
+    }
+}
+
+/// A cone with a base of `radius` around `base`, narrowing to a point at
+/// `apex`. Only the base is capped. Texture coordinates as for
+/// [`Cylinder`].
+#[derive(Clone, Copy, Debug)]
+pub struct Cone {
+    axis: Axis,
+    radius: f64,
+    // Radius lost per unit of height.
+    slope: f64,
+}
+
+impl Cone {
+    pub fn new(
+        base: Point3,
+        apex: Point3,
+        radius: f64,
+        capped: bool,
+        material: MaterialId,
+    ) -> Self {
+        let axis = Axis::new(base, apex, radius, capped, material);
+        Cone {
+            axis,
+            radius,
+            slope: radius / axis.height,
+        }
+    }
+}
+
+impl Profile for Cone {
+    fn axis(&self) -> &Axis {
+        &self.axis
+    }
+
+    fn coefficients(&self, o: Vec3, d: Vec3) -> (f64, f64, f64) {
+        let k = self.slope;
+        let radius_at_origin = self.radius - k * o.z;
+        (
+            d.x * d.x + d.y * d.y - k * k * d.z * d.z,
+            2.0 * (o.x * d.x + o.y * d.y + k * d.z * radius_at_origin),
+            o.x * o.x + o.y * o.y - radius_at_origin * radius_at_origin,
+        )
+    }
+
+    fn gradient(&self, p: Vec3) -> Vec3 {
+        Vec3::new(p.x, p.y, self.slope * self.radius_at(p.z))
+    }
+
+    fn radius_at(&self, z: f64) -> f64 {
+        self.radius - self.slope * z
+    }
+}
+
+/// A paraboloid bowl with its vertex at `vertex`, opening towards `top`
+/// where it reaches `radius`. Only the rim is capped. Texture coordinates
+/// as for [`Cylinder`].
+#[derive(Clone, Copy, Debug)]
+pub struct Paraboloid {
+    axis: Axis,
+    // r² = curvature * z
+    curvature: f64,
+}
+
+impl Paraboloid {
+    pub fn new(
+        vertex: Point3,
+        top: Point3,
+        radius: f64,
+        capped: bool,
+        material: MaterialId,
+    ) -> Self {
+        let axis = Axis::new(vertex, top, radius, capped, material);
+        Paraboloid {
+            axis,
+            curvature: radius * radius / axis.height,
+        }
+    }
+}
+
+impl Profile for Paraboloid {
+    fn axis(&self) -> &Axis {
+        &self.axis
+    }
+
+    fn coefficients(&self, o: Vec3, d: Vec3) -> (f64, f64, f64) {
+        (
+            d.x * d.x + d.y * d.y,
+            2.0 * (o.x * d.x + o.y * d.y) - self.curvature * d.z,
+            o.x * o.x + o.y * o.y - self.curvature * o.z,
+        )
+    }
+
+    fn gradient(&self, p: Vec3) -> Vec3 {
+        Vec3::new(2.0 * p.x, 2.0 * p.y, -self.curvature)
+    }
+
+    fn radius_at(&self, z: f64) -> f64 {
+        (self.curvature * z).max(0.0).sqrt()
+    }
+}
+
+/// A hyperboloid of one sheet between `base` and `top`: `radius` at both
+/// ends, narrowing to `waist_radius` halfway along. Equal radii give a
+/// cylinder. Texture coordinates as for [`Cylinder`].
+#[derive(Clone, Copy, Debug)]
+pub struct Hyperboloid {
+    axis: Axis,
+    waist_radius: f64,
+    // r² = waist_radius² + flare * (z - height / 2)²
+    flare: f64,
+}
+
+impl Hyperboloid {
+    pub fn new(
+        base: Point3,
+        top: Point3,
+        radius: f64,
+        waist_radius: f64,
+        capped: bool,
+        material: MaterialId,
+    ) -> Self {
+        let axis = Axis::new(base, top, radius.max(waist_radius), capped, material);
+        let half_height = 0.5 * axis.height;
+        Hyperboloid {
+            axis,
+            waist_radius,
+            flare: (radius * radius - waist_radius * waist_radius) / (half_height * half_height),
+        }
+    }
+}
+
+impl Profile for Hyperboloid {
+    fn axis(&self) -> &Axis {
+        &self.axis
+    }
+
+    fn coefficients(&self, o: Vec3, d: Vec3) -> (f64, f64, f64) {
+        let oz = o.z - 0.5 * self.axis.height;
+        (
+            d.x * d.x + d.y * d.y - self.flare * d.z * d.z,
+            2.0 * (o.x * d.x + o.y * d.y - self.flare * oz * d.z),
+            o.x * o.x + o.y * o.y - self.flare * oz * oz - self.waist_radius * self.waist_radius,
+        )
+    }
+
+    fn gradient(&self, p: Vec3) -> Vec3 {
+        Vec3::new(p.x, p.y, -self.flare * (p.z - 0.5 * self.axis.height))
+    }
+
+    fn radius_at(&self, z: f64) -> f64 {
+        let z = z - 0.5 * self.axis.height;
+        (self.waist_radius * self.waist_radius + self.flare * z * z)
+            .max(0.0)
+            .sqrt()
+    }
+}
+
+impl Hittable for Cylinder {
+    fn hit(&self, r: &Ray, ray_t: &Interval) -> Option<HitRecord> {
+        hit_profile(self, r, ray_t)
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        self.axis.bbox
+    }
+}
+
+impl Hittable for Cone {
+    fn hit(&self, r: &Ray, ray_t: &Interval) -> Option<HitRecord> {
+        hit_profile(self, r, ray_t)
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        self.axis.bbox
+    }
+}
+
+impl Hittable for Paraboloid {
+    fn hit(&self, r: &Ray, ray_t: &Interval) -> Option<HitRecord> {
+        hit_profile(self, r, ray_t)
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        self.axis.bbox
+    }
+}
+
+impl Hittable for Hyperboloid {
+    fn hit(&self, r: &Ray, ray_t: &Interval) -> Option<HitRecord> {
+        hit_profile(self, r, ray_t)
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        self.axis.bbox
+    }
+}
+
+/// A ring around `center` in the plane perpendicular to `axis`: a tube of
+/// `minor_radius` swept along a circle of `major_radius`. The texture
+/// coordinates run around the ring and around the tube.
+#[derive(Clone, Copy, Debug)]
+pub struct Torus {
+    frame: Frame,
+    major_radius: f64,
+    minor_radius: f64,
+    material: MaterialId,
+    bbox: Aabb,
+}
+
+impl Torus {
+    pub fn new(
+        center: Point3,
+        axis: Vec3,
+        major_radius: f64,
+        minor_radius: f64,
+        material: MaterialId,
+    ) -> Self {
+        let frame = Frame::new(center, axis);
+        let reach = major_radius + minor_radius;
+        Torus {
+            frame,
+            major_radius,
+            minor_radius,
+            material,
+            bbox: frame.bounding_box(
+                Vec3::new(-reach, -reach, -minor_radius),
+                Vec3::new(reach, reach, minor_radius),
+            ),
+        }
+    }
+}
+
+impl Hittable for Torus {
+    fn hit(&self, r: &Ray, ray_t: &Interval) -> Option<HitRecord> {
+        let d = self.frame.local(r.dir);
+        let length = d.length();
+        let d = d / length;
+
+        // Solve from the point on the ray closest to the centre: a distant
+        // origin makes the quartic's coefficients huge and its roots
+        // inaccurate.
+        let start = -self.frame.local_point(r.orig).dot(d);
+        let o = self.frame.local_point(r.orig) + start * d;
+
+        let major_sq = self.major_radius * self.major_radius;
+        let k = o.length_squared() + major_sq - self.minor_radius * self.minor_radius;
+        let f = o.dot(d);
+        let roots = solve_quartic(
+            1.0,
+            4.0 * f,
+            4.0 * f * f + 2.0 * k - 4.0 * major_sq * (d.x * d.x + d.y * d.y),
+            4.0 * f * k - 8.0 * major_sq * (o.x * d.x + o.y * d.y),
+            k * k - 4.0 * major_sq * (o.x * o.x + o.y * o.y),
+        );
+
+        let s = roots
+            .iter()
+            .copied()
+            .find(|&s| ray_t.surrounds((start + s) / length))?;
+        let t = (start + s) / length;
+
+        let p = o + s * d;
+        let ring = Vec3::new(p.x, p.y, 0.0);
+        let ring_distance = ring.length();
+        let tube_center = if ring_distance > 0.0 {
+            ring * (self.major_radius / ring_distance)
+        } else {
+            ring
+        };
+        let outward_normal = self.frame.world(p - tube_center).unit_vector();
+
+        let mut rec = HitRecord::build(t, r, &outward_normal, self.material);
+        rec.u = azimuth(p);
+        rec.v = (p.z.atan2(ring_distance - self.major_radius) + PI) / (2.0 * PI);
+        Some(rec)
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        self.bbox
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::material::{Material, Materials};
+
+    fn torus(center: Point3, axis: Vec3) -> Torus {
+        let mut materials = Materials::new();
+        let material = materials.add(Material::new_lambertian(Vec3::new(0.5, 0.5, 0.5)));
+        Torus::new(center, axis, 1.0, 0.25, material)
+    }
+
+    /// Distance from `p` to the tube of `torus(center, axis)` at unit major
+    /// radius, minus the tube's radius.
+    fn surface_distance(p: Point3, center: Point3, axis: Vec3) -> f64 {
+        let axis = axis.unit_vector();
+        let rel = p - center;
+        let height = rel.dot(axis);
+        let radial = (rel - height * axis).length();
+        ((radial - 1.0).powi(2) + height * height).sqrt() - 0.25
+    }
+
+    #[test]
+    fn torus_hit_from_distant_origin() {
+        let center = Point3::new(3.0, -2.0, 5.0);
+        let axis = Vec3::new(0.0, 1.0, 0.0);
+        let shape = torus(center, axis);
+        let everywhere = Interval::new(0.001, f64::INFINITY);
+
+        for distance in [10.0, 1e4, 1e6] {
+            // Straight at the outer rim.
+            let r = Ray::new(
+                center - Vec3::new(distance, 0.0, 0.0),
+                Vec3::new(1.0, 0.0, 0.0),
+            );
+            let rec = shape.hit(&r, &everywhere).expect("rim hit");
+            assert!((rec.t - (distance - 1.25)).abs() < 1e-9 * distance.max(1.0));
+            assert!((rec.normal - Vec3::new(-1.0, 0.0, 0.0)).near_zero());
+
+            // Down the axis, through the hole.
+            let r = Ray::new(center + distance * axis, -axis);
+            assert!(shape.hit(&r, &everywhere).is_none());
+
+            // Skimming just under the top of the tube, with an unnormalized
+            // direction. It enters where the tube's cross-section reaches
+            // height 0.2499, at radius 1 + √(0.25² - 0.2499²).
+            let dir = Vec3::new(0.0, 0.0, 7.0);
+            let r = Ray::new(center + Vec3::new(1.0, 0.2499, -distance), dir);
+            let rec = shape.hit(&r, &everywhere).expect("grazing hit");
+            let radius = 1.0 + (0.25f64.powi(2) - 0.2499f64.powi(2)).sqrt();
+            let entry = center + Vec3::new(1.0, 0.2499, -(radius * radius - 1.0).sqrt());
+            assert!(surface_distance(rec.p, center, axis).abs() < 1e-6);
+            assert!((rec.p - entry).length() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn torus_tilted_axis() {
+        let center = Point3::new(0.0, 0.0, 0.0);
+        let axis = Vec3::new(1.0, 1.0, 0.0);
+        let shape = torus(center, axis);
+        let everywhere = Interval::new(0.001, f64::INFINITY);
+
+        // Every hit along a fan of rays lies on the surface.
+        let mut hits = 0;
+        for i in 0..100 {
+            let angle = i as f64 * 0.0628;
+            let origin = Point3::new(1e3 * angle.cos(), 0.3, 1e3 * angle.sin());
+            let r = Ray::new(origin, center - origin + Vec3::new(0.0, 0.0, 0.6));
+            if let Some(rec) = shape.hit(&r, &everywhere) {
+                assert!(surface_distance(rec.p, center, axis).abs() < 1e-6);
+                assert!(rec.normal.dot(r.dir) < 0.0);
+                hits += 1;
+            }
+        }
+        assert!(hits > 50, "only {} hits", hits);
+    }
+}
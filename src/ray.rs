@@ -5,11 +5,30 @@ use crate::vec3::Vec3;
 pub struct Ray {
     pub orig: Point3,
     pub dir: Vec3,
+    /// When the ray was sent, for objects that move while the shutter is
+    /// open.
+    pub time: f64,
+    /// A uniform sample in [0, 1) that a
+    /// [`ConstantMedium`](crate::medium::ConstantMedium) turns into the
+    /// distance the ray travels through it before scattering. The camera
+    /// draws a fresh one from its sampler for every ray it traces; other rays
+    /// get 0.5, the median distance.
+    pub medium_sample: f64,
 }
 
 impl Ray {
+    /// A ray at time 0.
     pub fn new(orig: Point3, dir: Vec3) -> Self {
-        Ray { orig, dir }
+        Ray::with_time(orig, dir, 0.0)
+    }
+
+    pub fn with_time(orig: Point3, dir: Vec3, time: f64) -> Self {
+        Ray {
+            orig,
+            dir,
+            time,
+            medium_sample: 0.5,
+        }
     }
 
     pub fn at(&self, t: f64) -> Point3 {
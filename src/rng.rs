@@ -0,0 +1,63 @@
+/// A small, fast pseudo-random number generator (SplitMix64).
+///
+/// Every random decision made while rendering draws from an explicit `Rng`
+/// rather than a thread-local one, so a render depends only on its seed and
+/// not on how rayon schedules the work.
+#[derive(Clone, Debug)]
+pub struct Rng {
+    state: u64,
+}
+
+const GOLDEN_GAMMA: u64 = 0x9e37_79b9_7f4a_7c15;
+
+/// The SplitMix64 output function, a good 64-bit avalanche mixer.
+fn mix(mut z: u64) -> u64 {
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+/// Hashes a tuple of integers into 64 well-mixed bits.
+pub fn hash(values: &[u64]) -> u64 {
+    values
+        .iter()
+        .fold(0, |h, &v| mix(h.wrapping_add(GOLDEN_GAMMA) ^ v))
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Rng { state: mix(seed) }
+    }
+
+    /// The generator for one sample of one pixel. Each (seed, pixel, sample)
+    /// triple gets its own independent stream, so samples can be traced in
+    /// any order or on any thread and still produce the same image.
+    pub fn for_sample(seed: u64, x: u32, y: u32, sample: u32) -> Self {
+        let pixel = ((y as u64) << 32) | x as u64;
+        let h = mix(mix(seed) ^ pixel);
+        Rng {
+            state: mix(h ^ sample as u64),
+        }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(GOLDEN_GAMMA);
+        mix(self.state)
+    }
+
+    /// A uniform value in [0, 1) with 53 bits of precision.
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 * (1.0 / (1u64 << 53) as f64)
+    }
+
+    /// A uniform value in [min, max).
+    pub fn range(&mut self, min: f64, max: f64) -> f64 {
+        min + (max - min) * self.next_f64()
+    }
+
+    /// A uniform integer in [0, n). `n` must be non-zero.
+    pub fn below(&mut self, n: usize) -> usize {
+        // Multiply-shift keeps the bias below 2^-64 * n.
+        ((self.next_u64() as u128 * n as u128) >> 64) as usize
+    }
+}
@@ -0,0 +1,384 @@
+use std::f64::consts::PI;
+
+use serde::Deserialize;
+
+use crate::{
+    rng::{hash, Rng},
+    vec3::Vec3,
+};
+
+/// A source of sample values in [0, 1) for one pixel sample at a time.
+///
+/// Each call to [`Sampler::get_1d`] or [`Sampler::get_2d`] consumes the next
+/// dimension of the current sample. The camera always requests dimensions in
+/// the same order (pixel offset, lens position, then one per scattering
+/// decision), so low-discrepancy samplers stratify each decision across the
+/// samples of a pixel.
+pub trait Sampler {
+    /// Starts sample `index` of pixel (x, y) and rewinds to the first
+    /// dimension.
+    fn start_pixel_sample(&mut self, x: u32, y: u32, index: u32);
+
+    fn get_1d(&mut self) -> f64;
+
+    fn get_2d(&mut self) -> [f64; 2];
+}
+
+/// Which [`Sampler`] a render uses.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SamplerKind {
+    /// Independent uniform random numbers.
+    #[default]
+    Independent,
+    /// Jittered strata, shuffled per pixel and dimension.
+    Stratified,
+    /// The Halton sequence with per-pixel Owen scrambling.
+    Halton,
+    /// Owen-scrambled Sobol' points, padded from shuffled 2D sets.
+    Sobol,
+}
+
+impl SamplerKind {
+    pub const ALL: [SamplerKind; 4] = [
+        SamplerKind::Independent,
+        SamplerKind::Stratified,
+        SamplerKind::Halton,
+        SamplerKind::Sobol,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            SamplerKind::Independent => "independent",
+            SamplerKind::Stratified => "stratified",
+            SamplerKind::Halton => "halton",
+            SamplerKind::Sobol => "sobol",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<SamplerKind> {
+        SamplerKind::ALL
+            .into_iter()
+            .find(|kind| kind.name() == name)
+    }
+
+    /// Creates a sampler for a render with the given seed and sample count.
+    pub fn build(self, seed: u64, samples_per_pixel: u32) -> Box<dyn Sampler> {
+        let state = SampleState::new(seed);
+        match self {
+            SamplerKind::Independent => Box::new(IndependentSampler { state }),
+            SamplerKind::Stratified => Box::new(StratifiedSampler {
+                state,
+                samples_per_pixel: samples_per_pixel.max(1),
+            }),
+            SamplerKind::Halton => Box::new(HaltonSampler { state }),
+            SamplerKind::Sobol => Box::new(SobolSampler { state }),
+        }
+    }
+}
+
+/// Bookkeeping shared by all samplers: which sample is being generated and a
+/// generator for any values not covered by the sampler's own pattern.
+struct SampleState {
+    seed: u64,
+    x: u32,
+    y: u32,
+    index: u32,
+    dimension: u32,
+    rng: Rng,
+}
+
+impl SampleState {
+    fn new(seed: u64) -> Self {
+        SampleState {
+            seed,
+            x: 0,
+            y: 0,
+            index: 0,
+            dimension: 0,
+            rng: Rng::for_sample(seed, 0, 0, 0),
+        }
+    }
+
+    fn start(&mut self, x: u32, y: u32, index: u32) {
+        self.x = x;
+        self.y = y;
+        self.index = index;
+        self.dimension = 0;
+        self.rng = Rng::for_sample(self.seed, x, y, index);
+    }
+
+    /// A hash of the seed, pixel and current dimension, plus `salt`. It is
+    /// the same for every sample of a pixel.
+    fn dimension_hash(&self, salt: u64) -> u64 {
+        hash(&[
+            self.seed,
+            self.x as u64,
+            self.y as u64,
+            self.dimension as u64,
+            salt,
+        ])
+    }
+}
+
+pub struct IndependentSampler {
+    state: SampleState,
+}
+
+impl Sampler for IndependentSampler {
+    fn start_pixel_sample(&mut self, x: u32, y: u32, index: u32) {
+        self.state.start(x, y, index);
+    }
+
+    fn get_1d(&mut self) -> f64 {
+        self.state.rng.next_f64()
+    }
+
+    fn get_2d(&mut self) -> [f64; 2] {
+        [self.state.rng.next_f64(), self.state.rng.next_f64()]
+    }
+}
+
+/// Jittered sampling: every dimension is split into `samples_per_pixel`
+/// strata (a near-square grid for 2D requests) and each sample of a pixel
+/// lands in a different one. The stratum order is shuffled per pixel and
+/// dimension so dimensions stay uncorrelated.
+pub struct StratifiedSampler {
+    state: SampleState,
+    samples_per_pixel: u32,
+}
+
+impl StratifiedSampler {
+    /// The stratum of the current sample among `count`. Samples beyond the
+    /// first `count` start a new, differently shuffled round.
+    fn stratum(&self, count: u32) -> u32 {
+        let round = self.state.index / count;
+        let h = self.state.dimension_hash(round as u64);
+        permutation_element(self.state.index % count, count, h as u32)
+    }
+}
+
+impl Sampler for StratifiedSampler {
+    fn start_pixel_sample(&mut self, x: u32, y: u32, index: u32) {
+        self.state.start(x, y, index);
+    }
+
+    fn get_1d(&mut self) -> f64 {
+        let n = self.samples_per_pixel;
+        let stratum = self.stratum(n);
+        self.state.dimension += 1;
+        (stratum as f64 + self.state.rng.next_f64()) / n as f64
+    }
+
+    fn get_2d(&mut self) -> [f64; 2] {
+        let nx = (self.samples_per_pixel as f64).sqrt().ceil() as u32;
+        let ny = self.samples_per_pixel.div_ceil(nx);
+        let stratum = self.stratum(nx * ny);
+        self.state.dimension += 1;
+        [
+            ((stratum % nx) as f64 + self.state.rng.next_f64()) / nx as f64,
+            ((stratum / nx) as f64 + self.state.rng.next_f64()) / ny as f64,
+        ]
+    }
+}
+
+const PRIMES: [u64; 64] = [
+    2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41, 43, 47, 53, 59, 61, 67, 71, 73, 79, 83, 89, 97,
+    101, 103, 107, 109, 113, 127, 131, 137, 139, 149, 151, 157, 163, 167, 173, 179, 181, 191, 193,
+    197, 199, 211, 223, 227, 229, 233, 239, 241, 251, 257, 263, 269, 271, 277, 281, 283, 293, 307,
+    311,
+];
+
+/// The Halton sequence, one prime base per dimension, indexed by the sample
+/// number and Owen-scrambled per pixel. Dimensions past the last base fall
+/// back to independent random numbers.
+pub struct HaltonSampler {
+    state: SampleState,
+}
+
+impl Sampler for HaltonSampler {
+    fn start_pixel_sample(&mut self, x: u32, y: u32, index: u32) {
+        self.state.start(x, y, index);
+    }
+
+    fn get_1d(&mut self) -> f64 {
+        let dimension = self.state.dimension as usize;
+        let value = match PRIMES.get(dimension) {
+            Some(&base) => owen_scrambled_radical_inverse(
+                base,
+                self.state.index as u64,
+                self.state.dimension_hash(0) as u32,
+            ),
+            None => self.state.rng.next_f64(),
+        };
+        self.state.dimension += 1;
+        value
+    }
+
+    fn get_2d(&mut self) -> [f64; 2] {
+        [self.get_1d(), self.get_1d()]
+    }
+}
+
+/// Owen-scrambled Sobol' points after Burley, "Practical Hash-based Owen
+/// Scrambling" (2020). Every request draws from the first two Sobol'
+/// dimensions; shuffling the sample index per dimension decorrelates
+/// successive requests ("padding").
+pub struct SobolSampler {
+    state: SampleState,
+}
+
+impl SobolSampler {
+    fn shuffled_index(&self) -> u32 {
+        nested_uniform_scramble(self.state.index, self.state.dimension_hash(0) as u32)
+    }
+}
+
+impl Sampler for SobolSampler {
+    fn start_pixel_sample(&mut self, x: u32, y: u32, index: u32) {
+        self.state.start(x, y, index);
+    }
+
+    fn get_1d(&mut self) -> f64 {
+        let index = self.shuffled_index();
+        let scramble = self.state.dimension_hash(1) as u32;
+        self.state.dimension += 1;
+        to_unit(nested_uniform_scramble(index.reverse_bits(), scramble))
+    }
+
+    fn get_2d(&mut self) -> [f64; 2] {
+        let index = self.shuffled_index();
+        let scramble = self.state.dimension_hash(1);
+        self.state.dimension += 1;
+        [
+            to_unit(nested_uniform_scramble(
+                index.reverse_bits(),
+                scramble as u32,
+            )),
+            to_unit(nested_uniform_scramble(
+                sobol_second_dimension(index),
+                (scramble >> 32) as u32,
+            )),
+        ]
+    }
+}
+
+fn to_unit(bits: u32) -> f64 {
+    bits as f64 * (1.0 / (1u64 << 32) as f64)
+}
+
+/// The second Sobol' dimension, whose generator matrix is Pascal's triangle
+/// mod 2. (The first is the bit reversal of the index.)
+fn sobol_second_dimension(mut index: u32) -> u32 {
+    let mut direction = 1u32 << 31;
+    let mut result = 0;
+    while index != 0 {
+        if index & 1 != 0 {
+            result ^= direction;
+        }
+        index >>= 1;
+        direction ^= direction >> 1;
+    }
+    result
+}
+
+/// A hash in which each output bit depends only on the input bits at or
+/// below it (Laine and Karras 2011, with Burley's constants).
+fn laine_karras_permutation(mut x: u32, seed: u32) -> u32 {
+    x = x.wrapping_add(seed);
+    x ^= x.wrapping_mul(0x6c50_b47c);
+    x ^= x.wrapping_mul(0xb82f_1e52);
+    x ^= x.wrapping_mul(0xc7af_e638);
+    x ^= x.wrapping_mul(0x8d22_f6e6);
+    x
+}
+
+/// Owen scrambling of a 32-bit binary fraction.
+fn nested_uniform_scramble(x: u32, seed: u32) -> u32 {
+    laine_karras_permutation(x.reverse_bits(), seed).reverse_bits()
+}
+
+/// The radical inverse of `a` in `base`, with each digit permuted by a hash
+/// of the digits before it.
+fn owen_scrambled_radical_inverse(base: u64, mut a: u64, seed: u32) -> f64 {
+    let inv_base = 1.0 / base as f64;
+    let limit = u64::MAX / base - base;
+
+    let mut reversed_digits = 0u64;
+    let mut inv_base_m = 1.0;
+    // Keep going past the last non-zero digit: scrambled zeros still matter.
+    while 1.0 - inv_base_m < 1.0 && reversed_digits < limit {
+        let next = a / base;
+        let digit = a - next * base;
+        let digit_hash = hash(&[seed as u64, reversed_digits]) as u32;
+        let digit = permutation_element(digit as u32, base as u32, digit_hash) as u64;
+        reversed_digits = reversed_digits * base + digit;
+        inv_base_m *= inv_base;
+        a = next;
+    }
+
+    (inv_base_m * reversed_digits as f64).min(1.0 - f64::EPSILON / 2.0)
+}
+
+/// Element `i` of a random permutation of 0..n chosen by `seed`, without
+/// storing the permutation (Kensler, "Correlated Multi-Jittered Sampling").
+fn permutation_element(mut i: u32, n: u32, seed: u32) -> u32 {
+    let mut w = n - 1;
+    w |= w >> 1;
+    w |= w >> 2;
+    w |= w >> 4;
+    w |= w >> 8;
+    w |= w >> 16;
+
+    loop {
+        i ^= seed;
+        i = i.wrapping_mul(0xe170_893d);
+        i ^= seed >> 16;
+        i ^= (i & w) >> 4;
+        i ^= seed >> 8;
+        i = i.wrapping_mul(0x0929_eb3f);
+        i ^= seed >> 23;
+        i ^= (i & w) >> 1;
+        i = i.wrapping_mul(1 | seed >> 27);
+        i = i.wrapping_mul(0x6935_fa69);
+        i ^= (i & w) >> 11;
+        i = i.wrapping_mul(0x74dc_b303);
+        i ^= (i & w) >> 2;
+        i = i.wrapping_mul(0x9e50_1cc3);
+        i ^= (i & w) >> 2;
+        i = i.wrapping_mul(0xc860_a3df);
+        i &= w;
+        i ^= i >> 5;
+        // Cycle-walk until the value falls inside 0..n.
+        if i < n {
+            break;
+        }
+    }
+
+    i.wrapping_add(seed) % n
+}
+
+/// Maps a point of the unit square to a uniformly distributed unit vector.
+pub fn sample_unit_sphere(u: [f64; 2]) -> Vec3 {
+    let z = 1.0 - 2.0 * u[0];
+    let r = (1.0 - z * z).max(0.0).sqrt();
+    let phi = 2.0 * PI * u[1];
+    Vec3::new(r * phi.cos(), r * phi.sin(), z)
+}
+
+/// Maps a point of the unit square to a uniformly distributed point of the
+/// unit disk in the xy plane, using Shirley and Chiu's concentric mapping.
+pub fn sample_unit_disk(u: [f64; 2]) -> Vec3 {
+    let ox = 2.0 * u[0] - 1.0;
+    let oy = 2.0 * u[1] - 1.0;
+    if ox == 0.0 && oy == 0.0 {
+        return Vec3::new(0.0, 0.0, 0.0);
+    }
+
+    let (r, theta) = if ox.abs() > oy.abs() {
+        (ox, PI / 4.0 * (oy / ox))
+    } else {
+        (oy, PI / 2.0 - PI / 4.0 * (ox / oy))
+    };
+    Vec3::new(r * theta.cos(), r * theta.sin(), 0.0)
+}
@@ -0,0 +1,1067 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt, fs,
+    ops::Range,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use serde::Deserialize;
+use toml::Spanned;
+
+use crate::{
+    animation::{AnimatedTransform, Pose},
+    bvh::Bvh,
+    camera::{AdaptiveSampling, Atmosphere, Background, Camera},
+    filter::{Filter, FilterKind},
+    hittable::Hittable,
+    hittable_list::HittableList,
+    image::Image,
+    instance::Instance,
+    material::{Material, MaterialId, Materials},
+    medium::ConstantMedium,
+    obj,
+    planar::{self, Disk, Plane, Quad, Triangle},
+    quadric::{Cone, Cylinder, Hyperboloid, Paraboloid, Torus},
+    sampler::SamplerKind,
+    sphere::Sphere,
+    texture::{
+        CheckerTexture, ImageTexture, NoiseKind, NoiseTexture, SolidColor, Texture, WrapMode,
+    },
+    tile::TileOrder,
+    tonemap::PostProcess,
+    vec3::{Color, Vec3},
+};
+
+#[derive(Debug)]
+pub enum SceneError {
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    Parse {
+        path: PathBuf,
+        line: usize,
+        column: usize,
+        message: String,
+    },
+    /// A mesh or image referenced by the scene failed to load.
+    Asset {
+        path: PathBuf,
+        line: usize,
+        column: usize,
+        source: Box<dyn std::error::Error + Send + Sync>,
+    },
+}
+
+impl fmt::Display for SceneError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SceneError::Io { path, source } => write!(f, "{}: {}", path.display(), source),
+            SceneError::Parse {
+                path,
+                line,
+                column,
+                message,
+            } => write!(f, "{}:{}:{}: {}", path.display(), line, column, message),
+            SceneError::Asset {
+                path,
+                line,
+                column,
+                source,
+            } => write!(f, "{}:{}:{}: {}", path.display(), line, column, source),
+        }
+    }
+}
+
+impl std::error::Error for SceneError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            SceneError::Io { source, .. } => Some(source),
+            SceneError::Parse { .. } => None,
+            SceneError::Asset { source, .. } => Some(source.as_ref()),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SceneDesc {
+    camera: Spanned<CameraDesc>,
+    #[serde(default)]
+    textures: BTreeMap<String, Spanned<TextureDesc>>,
+    #[serde(default)]
+    materials: BTreeMap<String, Spanned<MaterialDesc>>,
+    /// Objects built once and placed any number of times by `instance`
+    /// objects.
+    #[serde(default)]
+    prototypes: BTreeMap<String, Spanned<ObjectDesc>>,
+    #[serde(default)]
+    objects: Vec<Spanned<ObjectDesc>>,
+    #[serde(default)]
+    post: PostProcess,
+}
+
+/// Mirrors the arguments of `Camera::new`. Defaults match the book's camera.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields, default)]
+struct CameraDesc {
+    aspect_ratio: f64,
+    image_width: u32,
+    samples_per_pixel: u32,
+    max_depth: u32,
+    vfov: f64,
+    look_from: [f64; 3],
+    look_at: [f64; 3],
+    vup: [f64; 3],
+    defocus_angle: f64,
+    focus_dist: f64,
+    /// Rays are spread over the times from `shutter_open` to
+    /// `shutter_close`; moving objects blur across that interval.
+    shutter_open: f64,
+    shutter_close: f64,
+    /// Solid background color. The sky gradient is used when omitted.
+    background: Option<[f64; 3]>,
+    /// Fog filling the whole scene.
+    atmosphere: Option<Spanned<AtmosphereDesc>>,
+    seed: u64,
+    sampler: SamplerKind,
+    /// Enables adaptive sampling; `samples_per_pixel` becomes the average.
+    adaptive: Option<AdaptiveSampling>,
+    filter: FilterKind,
+    /// Defaults to the filter's own default radius.
+    filter_radius: Option<f64>,
+    tile_size: u32,
+    tile_order: TileOrder,
+}
+
+impl Default for CameraDesc {
+    fn default() -> Self {
+        CameraDesc {
+            aspect_ratio: 1.0,
+            image_width: 100,
+            samples_per_pixel: 10,
+            max_depth: 10,
+            vfov: 90.0,
+            look_from: [0.0, 0.0, 0.0],
+            look_at: [0.0, 0.0, -1.0],
+            vup: [0.0, 1.0, 0.0],
+            defocus_angle: 0.0,
+            focus_dist: 10.0,
+            shutter_open: 0.0,
+            shutter_close: 0.0,
+            background: None,
+            atmosphere: None,
+            seed: 0,
+            sampler: SamplerKind::default(),
+            adaptive: None,
+            filter: FilterKind::default(),
+            filter_radius: None,
+            tile_size: 16,
+            tile_order: TileOrder::default(),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct AtmosphereDesc {
+    density: f64,
+    #[serde(default = "default_albedo")]
+    albedo: [f64; 3],
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+enum TextureDesc {
+    Solid {
+        color: [f64; 3],
+    },
+    Checker {
+        scale: f64,
+        even: [f64; 3],
+        odd: [f64; 3],
+    },
+    Image {
+        path: String,
+        #[serde(default)]
+        wrap: WrapMode,
+    },
+    Noise {
+        scale: f64,
+        #[serde(default)]
+        kind: NoiseKind,
+    },
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+enum MaterialDesc {
+    Lambertian {
+        albedo: Option<[f64; 3]>,
+        texture: Option<String>,
+    },
+    Metal {
+        albedo: Option<[f64; 3]>,
+        texture: Option<String>,
+        #[serde(default)]
+        fuzz: f64,
+    },
+    Dielectric {
+        ir: f64,
+    },
+    DiffuseLight {
+        emit: Option<[f64; 3]>,
+        texture: Option<String>,
+    },
+    Isotropic {
+        albedo: Option<[f64; 3]>,
+        texture: Option<String>,
+    },
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+enum ObjectDesc {
+    /// With `center1`, the sphere moves from `center` at time 0 to
+    /// `center1` at time 1.
+    Sphere {
+        center: [f64; 3],
+        center1: Option<[f64; 3]>,
+        radius: f64,
+        material: String,
+    },
+    /// A parallelogram with a corner at `q` and edges `u` and `v`.
+    Quad {
+        q: [f64; 3],
+        u: [f64; 3],
+        v: [f64; 3],
+        material: String,
+    },
+    Triangle {
+        a: [f64; 3],
+        b: [f64; 3],
+        c: [f64; 3],
+        material: String,
+    },
+    Disk {
+        center: [f64; 3],
+        normal: [f64; 3],
+        radius: f64,
+        material: String,
+    },
+    /// An infinite plane.
+    Plane {
+        point: [f64; 3],
+        normal: [f64; 3],
+        material: String,
+    },
+    /// An axis-aligned box given by two opposite corners.
+    Box {
+        a: [f64; 3],
+        b: [f64; 3],
+        material: String,
+    },
+    /// Cylinders, cones, paraboloids and hyperboloids run along the axis
+    /// from `base` (the vertex, for a paraboloid) to `top` (the apex, for a
+    /// cone) and are closed with flat caps unless `capped` is false.
+    Cylinder {
+        base: [f64; 3],
+        top: [f64; 3],
+        radius: f64,
+        #[serde(default = "default_capped")]
+        capped: bool,
+        material: String,
+    },
+    Cone {
+        base: [f64; 3],
+        apex: [f64; 3],
+        radius: f64,
+        #[serde(default = "default_capped")]
+        capped: bool,
+        material: String,
+    },
+    Paraboloid {
+        vertex: [f64; 3],
+        top: [f64; 3],
+        radius: f64,
+        #[serde(default = "default_capped")]
+        capped: bool,
+        material: String,
+    },
+    /// `radius` at both ends, `waist_radius` halfway along.
+    Hyperboloid {
+        base: [f64; 3],
+        top: [f64; 3],
+        radius: f64,
+        waist_radius: f64,
+        #[serde(default = "default_capped")]
+        capped: bool,
+        material: String,
+    },
+    /// A ring in the plane perpendicular to `axis`.
+    Torus {
+        center: [f64; 3],
+        axis: [f64; 3],
+        major_radius: f64,
+        minor_radius: f64,
+        material: String,
+    },
+    /// A Wavefront OBJ file. `material` applies to faces without a `usemtl`.
+    Mesh {
+        path: String,
+        material: Option<String>,
+    },
+    /// Smoke or fog of uniform `density` filling the closed surface of a
+    /// prototype, scattered by `material` (normally an isotropic one).
+    ConstantMedium {
+        boundary: String,
+        density: f64,
+        material: String,
+    },
+    /// A copy of a prototype, scaled, then rotated around the x, y and z
+    /// axes in that order (in degrees), then translated. With `keyframes`,
+    /// the pose changes over time instead.
+    Instance {
+        prototype: String,
+        scale: Option<[f64; 3]>,
+        rotate: Option<[f64; 3]>,
+        translate: Option<[f64; 3]>,
+        #[serde(default)]
+        keyframes: Vec<KeyframeDesc>,
+    },
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct KeyframeDesc {
+    time: f64,
+    #[serde(default = "default_scale")]
+    scale: [f64; 3],
+    #[serde(default)]
+    rotate: [f64; 3],
+    #[serde(default)]
+    translate: [f64; 3],
+}
+
+fn default_capped() -> bool {
+    true
+}
+
+fn default_scale() -> [f64; 3] {
+    [1.0, 1.0, 1.0]
+}
+
+fn default_albedo() -> [f64; 3] {
+    [1.0, 1.0, 1.0]
+}
+
+fn vec3(v: [f64; 3]) -> Vec3 {
+    Vec3::new(v[0], v[1], v[2])
+}
+
+/// A world, its materials and the camera looking at it, as described by a
+/// TOML scene file, plus how the render should be turned into an 8-bit image.
+pub struct Scene {
+    pub camera: Camera,
+    pub world: HittableList,
+    pub materials: Materials,
+    pub post: PostProcess,
+}
+
+struct Loader<'a> {
+    path: &'a Path,
+    text: &'a str,
+    base_dir: &'a Path,
+    textures: HashMap<String, Arc<dyn Texture + Send + Sync>>,
+    materials: HashMap<String, MaterialId>,
+    prototypes: HashMap<String, Arc<dyn Hittable + Send + Sync>>,
+}
+
+impl Scene {
+    pub fn load(path: impl AsRef<Path>) -> Result<Scene, SceneError> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path).map_err(|source| SceneError::Io {
+            path: path.to_path_buf(),
+            source,
+        })?;
+
+        Scene::parse(&text, path)
+    }
+
+    /// Builds a scene from TOML source. `path` is used in error messages and
+    /// to resolve relative asset paths.
+    pub fn parse(text: &str, path: &Path) -> Result<Scene, SceneError> {
+        let mut loader = Loader {
+            path,
+            text,
+            base_dir: path.parent().unwrap_or(Path::new("")),
+            textures: HashMap::new(),
+            materials: HashMap::new(),
+            prototypes: HashMap::new(),
+        };
+
+        let desc: SceneDesc = toml::from_str(text)
+            .map_err(|e| loader.error(e.span().unwrap_or(0..0), e.message().to_string()))?;
+
+        for (name, texture) in &desc.textures {
+            let built = loader.build_texture(texture)?;
+            loader.textures.insert(name.clone(), built);
+        }
+
+        let mut materials = Materials::new();
+        for (name, material) in &desc.materials {
+            let id = materials.add(loader.build_material(material)?);
+            loader.materials.insert(name.clone(), id);
+        }
+
+        for (name, prototype) in &desc.prototypes {
+            if let ObjectDesc::Instance { .. } = prototype.get_ref() {
+                return Err(loader.error(
+                    prototype.span(),
+                    "a prototype cannot itself be an instance".to_string(),
+                ));
+            }
+            let mut parts = HittableList::new();
+            loader.add_object(&mut parts, &mut materials, prototype)?;
+            let built: Arc<dyn Hittable + Send + Sync> = match parts.objects.len() {
+                1 => Arc::from(parts.objects.pop().unwrap()),
+                _ => Arc::new(Bvh::new(parts)),
+            };
+            loader.prototypes.insert(name.clone(), built);
+        }
+
+        let mut world = HittableList::new();
+        for object in &desc.objects {
+            loader.add_object(&mut world, &mut materials, object)?;
+        }
+
+        let c = desc.camera.get_ref();
+        let positive = [
+            ("image_width", c.image_width > 0),
+            ("samples_per_pixel", c.samples_per_pixel > 0),
+            ("max_depth", c.max_depth > 0),
+            ("tile_size", c.tile_size > 0),
+            ("aspect_ratio", c.aspect_ratio > 0.0),
+        ];
+        if let Some((name, _)) = positive.iter().find(|(_, ok)| !ok) {
+            return Err(loader.error(desc.camera.span(), format!("`{}` must be positive", name)));
+        }
+        if let Some(radius) = c.filter_radius {
+            loader.positive(desc.camera.span(), "filter_radius", radius)?;
+        }
+        let view = vec3(c.look_from) - vec3(c.look_at);
+        if view.near_zero() {
+            return Err(loader.error(
+                desc.camera.span(),
+                "`look_from` and `look_at` coincide".to_string(),
+            ));
+        }
+        if view.unit_vector().cross(vec3(c.vup)).near_zero() {
+            return Err(loader.error(
+                desc.camera.span(),
+                "`vup` must not be zero or parallel to the view direction".to_string(),
+            ));
+        }
+        let mut camera = Camera::new(
+            c.aspect_ratio,
+            c.image_width,
+            c.samples_per_pixel,
+            c.max_depth,
+            c.vfov,
+            vec3(c.look_from),
+            vec3(c.look_at),
+            vec3(c.vup),
+            c.defocus_angle,
+            c.focus_dist,
+        );
+        camera.shutter_open = c.shutter_open;
+        camera.shutter_close = c.shutter_close;
+        if let Some(background) = c.background {
+            camera.background = Background::Solid(vec3(background));
+        }
+        if let Some(atmosphere) = &c.atmosphere {
+            let span = atmosphere.span();
+            let atmosphere = atmosphere.get_ref();
+            if atmosphere.density <= 0.0 {
+                return Err(loader.error(span, "`density` must be positive".to_string()));
+            }
+            camera.atmosphere = Some(Atmosphere {
+                density: atmosphere.density,
+                albedo: vec3(atmosphere.albedo),
+            });
+        }
+        camera.seed = c.seed;
+        camera.sampler = c.sampler;
+        camera.adaptive = c.adaptive;
+        camera.filter = Filter::new(c.filter);
+        if let Some(radius) = c.filter_radius {
+            camera.filter.radius = radius;
+        }
+        camera.tile_size = c.tile_size;
+        camera.tile_order = c.tile_order;
+
+        Ok(Scene {
+            camera,
+            world,
+            materials,
+            post: desc.post,
+        })
+    }
+}
+
+impl Loader<'_> {
+    fn line_column(&self, offset: usize) -> (usize, usize) {
+        let before = &self.text[..offset.min(self.text.len())];
+        let line = before.matches('\n').count() + 1;
+        let column = before.chars().rev().take_while(|&c| c != '\n').count() + 1;
+        (line, column)
+    }
+
+    fn error(&self, span: Range<usize>, message: String) -> SceneError {
+        let (line, column) = self.line_column(span.start);
+        SceneError::Parse {
+            path: self.path.to_path_buf(),
+            line,
+            column,
+            message,
+        }
+    }
+
+    fn asset_error(
+        &self,
+        span: Range<usize>,
+        source: impl std::error::Error + Send + Sync + 'static,
+    ) -> SceneError {
+        let (line, column) = self.line_column(span.start);
+        SceneError::Asset {
+            path: self.path.to_path_buf(),
+            line,
+            column,
+            source: Box::new(source),
+        }
+    }
+
+    fn texture(
+        &self,
+        span: Range<usize>,
+        name: &str,
+    ) -> Result<Arc<dyn Texture + Send + Sync>, SceneError> {
+        self.textures
+            .get(name)
+            .cloned()
+            .ok_or_else(|| self.error(span, format!("unknown texture `{}`", name)))
+    }
+
+    fn material(&self, span: Range<usize>, name: &str) -> Result<MaterialId, SceneError> {
+        self.materials
+            .get(name)
+            .copied()
+            .ok_or_else(|| self.error(span, format!("unknown material `{}`", name)))
+    }
+
+    fn prototype(
+        &self,
+        span: Range<usize>,
+        name: &str,
+    ) -> Result<&Arc<dyn Hittable + Send + Sync>, SceneError> {
+        self.prototypes
+            .get(name)
+            .ok_or_else(|| self.error(span, format!("unknown prototype `{}`", name)))
+    }
+
+    fn normal(&self, span: Range<usize>, normal: [f64; 3]) -> Result<Vec3, SceneError> {
+        let normal = vec3(normal);
+        if normal.near_zero() {
+            return Err(self.error(span, "`normal` must not be zero".to_string()));
+        }
+        Ok(normal)
+    }
+
+    /// A length or factor, which must be a positive number.
+    fn positive(&self, span: Range<usize>, name: &str, value: f64) -> Result<f64, SceneError> {
+        if value > 0.0 && value.is_finite() {
+            Ok(value)
+        } else {
+            Err(self.error(span, format!("`{}` must be positive and finite", name)))
+        }
+    }
+
+    /// Checks what [`Pose::is_valid`] requires, naming the field at fault.
+    fn check_pose(&self, span: Range<usize>, pose: &Pose) -> Result<(), SceneError> {
+        let fields = [
+            ("scale", pose.scale),
+            ("rotate", pose.rotate),
+            ("translate", pose.translate),
+        ];
+        for (name, v) in fields {
+            if !(v.x.is_finite() && v.y.is_finite() && v.z.is_finite()) {
+                return Err(self.error(span, format!("`{}` must be finite", name)));
+            }
+        }
+        if !pose.is_valid() {
+            return Err(self.error(span, "`scale` must not be zero".to_string()));
+        }
+        Ok(())
+    }
+
+    /// The two ends of a shape's axis, which must not coincide.
+    fn segment(
+        &self,
+        span: Range<usize>,
+        from: [f64; 3],
+        to: [f64; 3],
+    ) -> Result<(Vec3, Vec3), SceneError> {
+        let (from, to) = (vec3(from), vec3(to));
+        if (to - from).near_zero() {
+            return Err(self.error(span, "the ends of the axis coincide".to_string()));
+        }
+        Ok((from, to))
+    }
+
+    /// Resolves the mutually exclusive color/texture pair most materials take.
+    fn color_or_texture(
+        &self,
+        span: Range<usize>,
+        color_key: &str,
+        color: Option<[f64; 3]>,
+        texture: &Option<String>,
+    ) -> Result<Arc<dyn Texture + Send + Sync>, SceneError> {
+        match (color, texture) {
+            (Some(color), None) => Ok(Arc::new(SolidColor::new(vec3(color)))),
+            (None, Some(name)) => self.texture(span, name),
+            (Some(_), Some(_)) => Err(self.error(
+                span,
+                format!("`{}` and `texture` are mutually exclusive", color_key),
+            )),
+            (None, None) => Err(self.error(
+                span,
+                format!("missing field `{}` (or `texture`)", color_key),
+            )),
+        }
+    }
+
+    fn build_texture(
+        &self,
+        texture: &Spanned<TextureDesc>,
+    ) -> Result<Arc<dyn Texture + Send + Sync>, SceneError> {
+        Ok(match texture.get_ref() {
+            TextureDesc::Solid { color } => Arc::new(SolidColor::new(vec3(*color))),
+            TextureDesc::Checker { scale, even, odd } => {
+                let scale = self.positive(texture.span(), "scale", *scale)?;
+                Arc::new(CheckerTexture::from_colors(scale, vec3(*even), vec3(*odd)))
+            }
+            TextureDesc::Image { path, wrap } => {
+                let image = Image::load(self.base_dir.join(path))
+                    .map_err(|e| self.asset_error(texture.span(), e))?;
+                Arc::new(ImageTexture::new(Arc::new(image), *wrap))
+            }
+            TextureDesc::Noise { scale, kind } => Arc::new(NoiseTexture::new(*scale, *kind)),
+        })
+    }
+
+    fn build_material(&self, material: &Spanned<MaterialDesc>) -> Result<Material, SceneError> {
+        let span = material.span();
+        Ok(match material.get_ref() {
+            MaterialDesc::Lambertian { albedo, texture } => Material::new_lambertian_texture(
+                self.color_or_texture(span, "albedo", *albedo, texture)?,
+            ),
+            MaterialDesc::Metal {
+                albedo,
+                texture,
+                fuzz,
+            } => Material::new_metal_texture(
+                self.color_or_texture(span, "albedo", *albedo, texture)?,
+                *fuzz,
+            ),
+            MaterialDesc::Dielectric { ir } => {
+                Material::new_dielectric(self.positive(span, "ir", *ir)?)
+            }
+            MaterialDesc::DiffuseLight { emit, texture } => Material::new_diffuse_light_texture(
+                self.color_or_texture(span, "emit", *emit, texture)?,
+            ),
+            MaterialDesc::Isotropic { albedo, texture } => Material::new_isotropic_texture(
+                self.color_or_texture(span, "albedo", *albedo, texture)?,
+            ),
+        })
+    }
+
+    fn add_object(
+        &self,
+        world: &mut HittableList,
+        materials: &mut Materials,
+        object: &Spanned<ObjectDesc>,
+    ) -> Result<(), SceneError> {
+        let span = object.span();
+        match object.get_ref() {
+            ObjectDesc::Sphere {
+                center,
+                center1,
+                radius,
+                material,
+            } => {
+                let radius = self.positive(span.clone(), "radius", *radius)?;
+                let material = self.material(span, material)?;
+                let center1 = center1.unwrap_or(*center);
+                world.add(Box::new(Sphere::new_moving(
+                    vec3(*center),
+                    vec3(center1),
+                    radius,
+                    material,
+                )));
+            }
+            ObjectDesc::Quad { q, u, v, material } => {
+                let (u, v) = (vec3(*u), vec3(*v));
+                if u.cross(v).near_zero() {
+                    return Err(self.error(span, "quad edges `u` and `v` are parallel".to_string()));
+                }
+                let material = self.material(span, material)?;
+                world.add(Box::new(Quad::new(vec3(*q), u, v, material)));
+            }
+            ObjectDesc::Triangle { a, b, c, material } => {
+                let (a, b, c) = (vec3(*a), vec3(*b), vec3(*c));
+                if (b - a).cross(c - a).near_zero() {
+                    return Err(self.error(span, "triangle has no area".to_string()));
+                }
+                let material = self.material(span, material)?;
+                world.add(Box::new(Triangle::new(a, b, c, material)));
+            }
+            ObjectDesc::Disk {
+                center,
+                normal,
+                radius,
+                material,
+            } => {
+                let normal = self.normal(span.clone(), *normal)?;
+                let radius = self.positive(span.clone(), "radius", *radius)?;
+                let material = self.material(span, material)?;
+                world.add(Box::new(Disk::new(vec3(*center), normal, radius, material)));
+            }
+            ObjectDesc::Plane {
+                point,
+                normal,
+                material,
+            } => {
+                let normal = self.normal(span.clone(), *normal)?;
+                let material = self.material(span, material)?;
+                world.add(Box::new(Plane::new(vec3(*point), normal, material)));
+            }
+            ObjectDesc::Box { a, b, material } => {
+                let material = self.material(span, material)?;
+                for side in planar::make_box(vec3(*a), vec3(*b), material).objects {
+                    world.add(side);
+                }
+            }
+            ObjectDesc::Cylinder {
+                base,
+                top,
+                radius,
+                capped,
+                material,
+            } => {
+                let (base, top) = self.segment(span.clone(), *base, *top)?;
+                let radius = self.positive(span.clone(), "radius", *radius)?;
+                let material = self.material(span, material)?;
+                world.add(Box::new(Cylinder::new(
+                    base, top, radius, *capped, material,
+                )));
+            }
+            ObjectDesc::Cone {
+                base,
+                apex,
+                radius,
+                capped,
+                material,
+            } => {
+                let (base, apex) = self.segment(span.clone(), *base, *apex)?;
+                let radius = self.positive(span.clone(), "radius", *radius)?;
+                let material = self.material(span, material)?;
+                world.add(Box::new(Cone::new(base, apex, radius, *capped, material)));
+            }
+            ObjectDesc::Paraboloid {
+                vertex,
+                top,
+                radius,
+                capped,
+                material,
+            } => {
+                let (vertex, top) = self.segment(span.clone(), *vertex, *top)?;
+                let radius = self.positive(span.clone(), "radius", *radius)?;
+                let material = self.material(span, material)?;
+                world.add(Box::new(Paraboloid::new(
+                    vertex, top, radius, *capped, material,
+                )));
+            }
+            ObjectDesc::Hyperboloid {
+                base,
+                top,
+                radius,
+                waist_radius,
+                capped,
+                material,
+            } => {
+                let (base, top) = self.segment(span.clone(), *base, *top)?;
+                let radius = self.positive(span.clone(), "radius", *radius)?;
+                let material = self.material(span, material)?;
+                world.add(Box::new(Hyperboloid::new(
+                    base,
+                    top,
+                    radius,
+                    *waist_radius,
+                    *capped,
+                    material,
+                )));
+            }
+            ObjectDesc::Torus {
+                center,
+                axis,
+                major_radius,
+                minor_radius,
+                material,
+            } => {
+                if vec3(*axis).near_zero() {
+                    return Err(self.error(span, "`axis` must not be zero".to_string()));
+                }
+                let major_radius = self.positive(span.clone(), "major_radius", *major_radius)?;
+                let minor_radius = self.positive(span.clone(), "minor_radius", *minor_radius)?;
+                let material = self.material(span, material)?;
+                world.add(Box::new(Torus::new(
+                    vec3(*center),
+                    vec3(*axis),
+                    major_radius,
+                    minor_radius,
+                    material,
+                )));
+            }
+            ObjectDesc::Mesh { path, material } => {
+                let material = match material {
+                    Some(name) => self.material(span.clone(), name)?,
+                    None => materials.add(Material::new_lambertian(Color::new(0.8, 0.8, 0.8))),
+                };
+                let mesh = obj::load_obj(self.base_dir.join(path), materials, material)
+                    .map_err(|e| self.asset_error(span, e))?;
+                world.add(Box::new(mesh));
+            }
+            ObjectDesc::ConstantMedium {
+                boundary,
+                density,
+                material,
+            } => {
+                let boundary = self.prototype(span.clone(), boundary)?;
+                if *density <= 0.0 {
+                    return Err(self.error(span, "`density` must be positive".to_string()));
+                }
+                let material = self.material(span, material)?;
+                world.add(Box::new(ConstantMedium::new(
+                    Arc::clone(boundary),
+                    *density,
+                    material,
+                )));
+            }
+            ObjectDesc::Instance {
+                prototype,
+                scale,
+                rotate,
+                translate,
+                keyframes,
+            } => {
+                let object = self.prototype(span.clone(), prototype)?;
+
+                if keyframes.is_empty() {
+                    let pose = Pose {
+                        scale: vec3(scale.unwrap_or(default_scale())),
+                        rotate: vec3(rotate.unwrap_or_default()),
+                        translate: vec3(translate.unwrap_or_default()),
+                    };
+                    self.check_pose(span, &pose)?;
+                    world.add(Box::new(Instance::new(
+                        Arc::clone(object),
+                        pose.transform(),
+                    )));
+                    return Ok(());
+                }
+
+                if scale.is_some() || rotate.is_some() || translate.is_some() {
+                    return Err(self.error(
+                        span,
+                        "`keyframes` replaces `scale`, `rotate` and `translate`".to_string(),
+                    ));
+                }
+                let mut poses = Vec::with_capacity(keyframes.len());
+                for key in keyframes {
+                    if !key.time.is_finite() {
+                        return Err(self.error(span, "`time` must be finite".to_string()));
+                    }
+                    let pose = Pose {
+                        scale: vec3(key.scale),
+                        rotate: vec3(key.rotate),
+                        translate: vec3(key.translate),
+                    };
+                    self.check_pose(span.clone(), &pose)?;
+                    poses.push((key.time, pose));
+                }
+
+                // These are the checks `AnimatedTransform::new` asserts.
+                poses.sort_by(|a, b| a.0.total_cmp(&b.0));
+                for pair in poses.windows(2) {
+                    let ((t0, a), (t1, b)) = (pair[0], pair[1]);
+                    if a.flips_scale(&b) {
+                        return Err(self.error(
+                            span,
+                            format!(
+                                "`scale` changes sign between the keyframes at times {} and {}, \
+                                 so it would pass through zero",
+                                t0, t1
+                            ),
+                        ));
+                    }
+                }
+                world.add(Box::new(Instance::animated(
+                    Arc::clone(object),
+                    AnimatedTransform::new(poses),
+                )));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HEADER: &str = r#"
+[camera]
+image_width = 8
+
+[materials.m]
+type = "lambertian"
+albedo = [0.5, 0.5, 0.5]
+
+[prototypes.ball]
+type = "sphere"
+center = [0.0, 0.0, 0.0]
+radius = 1.0
+material = "m"
+"#;
+
+    /// The message of the error loading `HEADER` followed by `objects`.
+    fn error(objects: &str) -> String {
+        match Scene::parse(&format!("{}{}", HEADER, objects), Path::new("test.toml")) {
+            Ok(_) => panic!("scene loaded:\n{}", objects),
+            Err(e) => e.to_string(),
+        }
+    }
+
+    fn keyframes(first: &str, second: &str) -> String {
+        format!(
+            "[[objects]]\ntype = \"instance\"\nprototype = \"ball\"\n\
+             [[objects.keyframes]]\n{}\n[[objects.keyframes]]\n{}\n",
+            first, second
+        )
+    }
+
+    #[test]
+    fn rejects_invalid_keyframes() {
+        let cases = [
+            (
+                "time = 0.0\nscale = [nan, 1.0, 1.0]",
+                "time = 1.0\nscale = [nan, 1.0, 1.0]",
+                "`scale` must be finite",
+            ),
+            (
+                "time = 0.0\nrotate = [inf, 0.0, 0.0]",
+                "time = 1.0",
+                "`rotate` must be finite",
+            ),
+            (
+                "time = 0.0\ntranslate = [0.0, -inf, 0.0]",
+                "time = 1.0",
+                "`translate` must be finite",
+            ),
+            ("time = nan", "time = 1.0", "`time` must be finite"),
+            (
+                "time = 0.0\nscale = [0.0, 1.0, 1.0]",
+                "time = 1.0",
+                "`scale` must not be zero",
+            ),
+            (
+                "time = 1.0\nscale = [-1.0, 1.0, 1.0]",
+                "time = 0.0",
+                "changes sign",
+            ),
+        ];
+        for (first, second, expected) in cases {
+            let message = error(&keyframes(first, second));
+            assert!(message.contains(expected), "{}", message);
+            assert!(message.starts_with("test.toml:"), "{}", message);
+        }
+    }
+
+    #[test]
+    fn rejects_invalid_sizes() {
+        let cases = [
+            (
+                "[[objects]]\ntype = \"sphere\"\ncenter = [0.0, 0.0, 0.0]\nradius = 0.0\nmaterial = \"m\"",
+                "`radius` must be positive",
+            ),
+            (
+                "[[objects]]\ntype = \"disk\"\ncenter = [0.0, 0.0, 0.0]\nnormal = [0.0, 1.0, 0.0]\n\
+                 radius = -1.0\nmaterial = \"m\"",
+                "`radius` must be positive",
+            ),
+            (
+                "[[objects]]\ntype = \"cylinder\"\nbase = [0.0, 0.0, 0.0]\ntop = [0.0, 1.0, 0.0]\n\
+                 radius = nan\nmaterial = \"m\"",
+                "`radius` must be positive",
+            ),
+            (
+                "[[objects]]\ntype = \"torus\"\ncenter = [0.0, 0.0, 0.0]\naxis = [0.0, 1.0, 0.0]\n\
+                 major_radius = 1.0\nminor_radius = 0.0\nmaterial = \"m\"",
+                "`minor_radius` must be positive",
+            ),
+            (
+                "[textures.t]\ntype = \"checker\"\nscale = 0.0\neven = [0.0, 0.0, 0.0]\n\
+                 odd = [1.0, 1.0, 1.0]",
+                "`scale` must be positive",
+            ),
+            (
+                "[materials.glass]\ntype = \"dielectric\"\nir = inf",
+                "`ir` must be positive",
+            ),
+        ];
+        for (text, expected) in cases {
+            let message = error(text);
+            assert!(message.contains(expected), "{}", message);
+            assert!(message.starts_with("test.toml:"), "{}", message);
+        }
+    }
+
+    #[test]
+    fn rejects_invalid_camera() {
+        let cases = [
+            ("filter_radius = 0.0", "`filter_radius` must be positive"),
+            ("look_at = [0.0, 0.0, 0.0]", "coincide"),
+            ("vup = [0.0, 0.0, 2.0]", "parallel to the view direction"),
+        ];
+        for (setting, expected) in cases {
+            let text = HEADER.replace("image_width = 8", &format!("image_width = 8\n{}", setting));
+            let message = match Scene::parse(&text, Path::new("test.toml")) {
+                Ok(_) => panic!("scene loaded with {}", setting),
+                Err(e) => e.to_string(),
+            };
+            assert!(message.contains(expected), "{}", message);
+            assert!(message.starts_with("test.toml:2:"), "{}", message);
+        }
+    }
+
+    #[test]
+    fn accepts_negative_scale_kept_throughout() {
+        let text = keyframes(
+            "time = 0.0\nscale = [-1.0, 1.0, 1.0]",
+            "time = 1.0\nscale = [-2.0, 1.0, 1.0]",
+        );
+        Scene::parse(&format!("{}{}", HEADER, text), Path::new("test.toml")).unwrap();
+    }
+}
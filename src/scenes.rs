@@ -0,0 +1,610 @@
+use std::sync::Arc;
+
+use crate::{
+    camera::{Background, Camera},
+    hittable_list::HittableList,
+    instance::Instance,
+    material::{Material, Materials},
+    medium::ConstantMedium,
+    planar::{make_box, Plane, Quad},
+    quadric::{Cone, Cylinder, Hyperboloid, Paraboloid, Torus},
+    rng::Rng,
+    scene::Scene,
+    sphere::Sphere,
+    texture::{CheckerTexture, NoiseKind, NoiseTexture},
+    tonemap::PostProcess,
+    transform::Transform,
+    vec3::{Color, Point3, Vec3},
+};
+
+pub struct BuiltinScene {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub build: fn() -> Scene,
+}
+
+pub const BUILTIN_SCENES: &[BuiltinScene] = &[
+    BuiltinScene {
+        name: "random_spheres",
+        description: "The final scene of the first book: ~480 random spheres",
+        build: random_spheres,
+    },
+    BuiltinScene {
+        name: "bouncing_spheres",
+        description: "The random spheres with the small diffuse ones bouncing, motion blurred",
+        build: bouncing_spheres,
+    },
+    BuiltinScene {
+        name: "checkered_spheres",
+        description: "Two large spheres with a 3D checker texture",
+        build: checkered_spheres,
+    },
+    BuiltinScene {
+        name: "perlin_spheres",
+        description: "Marble-textured spheres using Perlin turbulence",
+        build: perlin_spheres,
+    },
+    BuiltinScene {
+        name: "simple_light",
+        description: "Marble spheres lit only by emissive spheres",
+        build: simple_light,
+    },
+    BuiltinScene {
+        name: "quads",
+        description: "Five coloured quads facing into the middle",
+        build: quads,
+    },
+    BuiltinScene {
+        name: "cornell_box",
+        description: "A Cornell box with two rotated white blocks and a ceiling light",
+        build: cornell_box,
+    },
+    BuiltinScene {
+        name: "cornell_smoke",
+        description: "The Cornell box with its blocks made of dark and light smoke",
+        build: cornell_smoke,
+    },
+    BuiltinScene {
+        name: "quadrics",
+        description: "Cylinders, a cone, a paraboloid, a hyperboloid and tori on a plane",
+        build: quadrics,
+    },
+];
+
+pub fn find(name: &str) -> Option<&'static BuiltinScene> {
+    BUILTIN_SCENES.iter().find(|scene| scene.name == name)
+}
+
+fn random_spheres() -> Scene {
+    spheres(false)
+}
+
+fn bouncing_spheres() -> Scene {
+    spheres(true)
+}
+
+/// The final scene of the first book. With `bouncing`, the small diffuse
+/// spheres move upwards while the shutter is open, as in the second book.
+fn spheres(bouncing: bool) -> Scene {
+    // A fixed seed keeps the layout the same from run to run.
+    let mut rng = Rng::new(0);
+
+    // World
+    let mut world: HittableList = HittableList::new();
+    let mut materials = Materials::new();
+
+    let ground_material = materials.add(Material::new_lambertian(Color::new(0.5, 0.5, 0.5)));
+    world.add(Box::new(Sphere::new(
+        Point3::new(0.0, -1000.0, 0.0),
+        1000.0,
+        ground_material,
+    )));
+
+    for a in (-11)..11 {
+        for b in (-11)..11 {
+            let choose_mat = rng.next_f64();
+            let center = Point3::new(
+                a as f64 + 0.9 * rng.next_f64(),
+                0.2,
+                b as f64 + 0.9 * rng.next_f64(),
+            );
+
+            if (center - Point3::new(4.0, 0.2, 0.0)).length() > 0.9 {
+                let sphere_material = if choose_mat < 0.8 {
+                    // diffuse
+                    let albedo = Color::random(&mut rng) * Color::random(&mut rng);
+                    Material::new_lambertian(albedo)
+                } else if choose_mat < 0.95 {
+                    // metal
+                    let albedo = Color::random_range(&mut rng, 0.5, 1.0);
+                    let fuzz = rng.next_f64() * 0.5;
+                    Material::new_metal(albedo, fuzz)
+                } else {
+                    // glass
+                    Material::new_dielectric(1.5)
+                };
+
+                let center1 = if bouncing && choose_mat < 0.8 {
+                    center + Vec3::new(0.0, rng.range(0.0, 0.5), 0.0)
+                } else {
+                    center
+                };
+
+                let sphere_material = materials.add(sphere_material);
+                world.add(Box::new(Sphere::new_moving(
+                    center,
+                    center1,
+                    0.2,
+                    sphere_material,
+                )));
+            }
+        }
+    }
+
+    let material1 = materials.add(Material::new_dielectric(1.5));
+    world.add(Box::new(Sphere::new(
+        Point3::new(0.0, 1.0, 0.0),
+        1.0,
+        material1,
+    )));
+
+    let material2 = materials.add(Material::new_lambertian(Color::new(0.4, 0.2, 0.1)));
+    world.add(Box::new(Sphere::new(
+        Point3::new(-4.0, 1.0, 0.0),
+        1.0,
+        material2,
+    )));
+
+    let material3 = materials.add(Material::new_metal(Color::new(0.7, 0.6, 0.5), 0.0));
+    world.add(Box::new(Sphere::new(
+        Point3::new(4.0, 1.0, 0.0),
+        1.0,
+        material3,
+    )));
+
+    // Camera
+    let aspect_ratio = 16.0 / 9.0;
+    let image_width: u32 = 1200;
+    let samples_per_pixel: u32 = 500;
+    let max_depth: u32 = 50;
+    let vfov: f64 = 20.0;
+    let look_from = Point3::new(13.0, 2.0, 3.0);
+    let look_at = Point3::new(0.0, 0.0, 0.0);
+    let vup = Point3::new(0.0, 1.0, 0.0);
+
+    let defocus_angle = 0.6;
+    let focus_dist = 10.0;
+
+    let mut camera = Camera::new(
+        aspect_ratio,
+        image_width,
+        samples_per_pixel,
+        max_depth,
+        vfov,
+        look_from,
+        look_at,
+        vup,
+        defocus_angle,
+        focus_dist,
+    );
+    if bouncing {
+        camera.shutter_close = 1.0;
+    }
+
+    Scene {
+        camera,
+        world,
+        materials,
+        post: PostProcess::default(),
+    }
+}
+
+fn checkered_spheres() -> Scene {
+    let mut world = HittableList::new();
+    let mut materials = Materials::new();
+
+    let checker = materials.add(Material::new_lambertian_texture(Arc::new(
+        CheckerTexture::from_colors(0.32, Color::new(0.2, 0.3, 0.1), Color::new(0.9, 0.9, 0.9)),
+    )));
+
+    world.add(Box::new(Sphere::new(
+        Point3::new(0.0, -10.0, 0.0),
+        10.0,
+        checker,
+    )));
+    world.add(Box::new(Sphere::new(
+        Point3::new(0.0, 10.0, 0.0),
+        10.0,
+        checker,
+    )));
+
+    let camera = Camera::new(
+        16.0 / 9.0,
+        400,
+        100,
+        50,
+        20.0,
+        Point3::new(13.0, 2.0, 3.0),
+        Point3::new(0.0, 0.0, 0.0),
+        Point3::new(0.0, 1.0, 0.0),
+        0.0,
+        10.0,
+    );
+
+    Scene {
+        camera,
+        world,
+        materials,
+        post: PostProcess::default(),
+    }
+}
+
+fn perlin_world(materials: &mut Materials) -> HittableList {
+    let mut world = HittableList::new();
+
+    let marble = materials.add(Material::new_lambertian_texture(Arc::new(
+        NoiseTexture::new(4.0, NoiseKind::Marble),
+    )));
+    world.add(Box::new(Sphere::new(
+        Point3::new(0.0, -1000.0, 0.0),
+        1000.0,
+        marble,
+    )));
+    world.add(Box::new(Sphere::new(
+        Point3::new(0.0, 2.0, 0.0),
+        2.0,
+        marble,
+    )));
+
+    world
+}
+
+fn perlin_spheres() -> Scene {
+    let mut materials = Materials::new();
+    let world = perlin_world(&mut materials);
+
+    let camera = Camera::new(
+        16.0 / 9.0,
+        400,
+        100,
+        50,
+        20.0,
+        Point3::new(13.0, 2.0, 3.0),
+        Point3::new(0.0, 0.0, 0.0),
+        Point3::new(0.0, 1.0, 0.0),
+        0.0,
+        10.0,
+    );
+
+    Scene {
+        camera,
+        world,
+        materials,
+        post: PostProcess::default(),
+    }
+}
+
+fn simple_light() -> Scene {
+    let mut materials = Materials::new();
+    let mut world = perlin_world(&mut materials);
+
+    let light = materials.add(Material::new_diffuse_light(Color::new(4.0, 4.0, 4.0)));
+    world.add(Box::new(Sphere::new(
+        Point3::new(0.0, 7.0, 0.0),
+        2.0,
+        light,
+    )));
+    world.add(Box::new(Sphere::new(
+        Point3::new(4.0, 2.0, -3.0),
+        1.0,
+        light,
+    )));
+
+    let mut camera = Camera::new(
+        16.0 / 9.0,
+        400,
+        100,
+        50,
+        20.0,
+        Point3::new(26.0, 3.0, 6.0),
+        Point3::new(0.0, 2.0, 0.0),
+        Point3::new(0.0, 1.0, 0.0),
+        0.0,
+        10.0,
+    );
+    camera.background = Background::Solid(Color::new(0.0, 0.0, 0.0));
+
+    Scene {
+        camera,
+        world,
+        materials,
+        post: PostProcess::default(),
+    }
+}
+
+fn quads() -> Scene {
+    let mut world = HittableList::new();
+    let mut materials = Materials::new();
+
+    let left_red = materials.add(Material::new_lambertian(Color::new(1.0, 0.2, 0.2)));
+    let back_green = materials.add(Material::new_lambertian(Color::new(0.2, 1.0, 0.2)));
+    let right_blue = materials.add(Material::new_lambertian(Color::new(0.2, 0.2, 1.0)));
+    let upper_orange = materials.add(Material::new_lambertian(Color::new(1.0, 0.5, 0.0)));
+    let lower_teal = materials.add(Material::new_lambertian(Color::new(0.2, 0.8, 0.8)));
+
+    let quads = [
+        (
+            (-3.0, -2.0, 5.0),
+            (0.0, 0.0, -4.0),
+            (0.0, 4.0, 0.0),
+            left_red,
+        ),
+        (
+            (-2.0, -2.0, 0.0),
+            (4.0, 0.0, 0.0),
+            (0.0, 4.0, 0.0),
+            back_green,
+        ),
+        (
+            (3.0, -2.0, 1.0),
+            (0.0, 0.0, 4.0),
+            (0.0, 4.0, 0.0),
+            right_blue,
+        ),
+        (
+            (-2.0, 3.0, 1.0),
+            (4.0, 0.0, 0.0),
+            (0.0, 0.0, 4.0),
+            upper_orange,
+        ),
+        (
+            (-2.0, -3.0, 5.0),
+            (4.0, 0.0, 0.0),
+            (0.0, 0.0, -4.0),
+            lower_teal,
+        ),
+    ];
+    for (q, u, v, material) in quads {
+        world.add(Box::new(Quad::new(
+            Point3::new(q.0, q.1, q.2),
+            Vec3::new(u.0, u.1, u.2),
+            Vec3::new(v.0, v.1, v.2),
+            material,
+        )));
+    }
+
+    let camera = Camera::new(
+        1.0,
+        400,
+        100,
+        50,
+        80.0,
+        Point3::new(0.0, 0.0, 9.0),
+        Point3::new(0.0, 0.0, 0.0),
+        Point3::new(0.0, 1.0, 0.0),
+        0.0,
+        10.0,
+    );
+
+    Scene {
+        camera,
+        world,
+        materials,
+        post: PostProcess::default(),
+    }
+}
+
+fn cornell_box() -> Scene {
+    cornell(false)
+}
+
+fn cornell_smoke() -> Scene {
+    cornell(true)
+}
+
+/// The Cornell box, with solid blocks or, as in the second book, blocks of
+/// smoke under a larger and dimmer light.
+fn cornell(smoke: bool) -> Scene {
+    let mut world = HittableList::new();
+    let mut materials = Materials::new();
+
+    let red = materials.add(Material::new_lambertian(Color::new(0.65, 0.05, 0.05)));
+    let white = materials.add(Material::new_lambertian(Color::new(0.73, 0.73, 0.73)));
+    let green = materials.add(Material::new_lambertian(Color::new(0.12, 0.45, 0.15)));
+    let (light, light_q, light_u, light_v) = if smoke {
+        (
+            Color::new(7.0, 7.0, 7.0),
+            (113.0, 554.0, 127.0),
+            (330.0, 0.0, 0.0),
+            (0.0, 0.0, 305.0),
+        )
+    } else {
+        (
+            Color::new(15.0, 15.0, 15.0),
+            (343.0, 554.0, 332.0),
+            (-130.0, 0.0, 0.0),
+            (0.0, 0.0, -105.0),
+        )
+    };
+    let light = materials.add(Material::new_diffuse_light(light));
+
+    let walls = [
+        (
+            (555.0, 0.0, 0.0),
+            (0.0, 555.0, 0.0),
+            (0.0, 0.0, 555.0),
+            green,
+        ),
+        ((0.0, 0.0, 0.0), (0.0, 555.0, 0.0), (0.0, 0.0, 555.0), red),
+        (light_q, light_u, light_v, light),
+        ((0.0, 0.0, 0.0), (555.0, 0.0, 0.0), (0.0, 0.0, 555.0), white),
+        (
+            (555.0, 555.0, 555.0),
+            (-555.0, 0.0, 0.0),
+            (0.0, 0.0, -555.0),
+            white,
+        ),
+        (
+            (0.0, 0.0, 555.0),
+            (555.0, 0.0, 0.0),
+            (0.0, 555.0, 0.0),
+            white,
+        ),
+    ];
+    for (q, u, v, material) in walls {
+        world.add(Box::new(Quad::new(
+            Point3::new(q.0, q.1, q.2),
+            Vec3::new(u.0, u.1, u.2),
+            Vec3::new(v.0, v.1, v.2),
+            material,
+        )));
+    }
+
+    // Two blocks built at the origin, then turned and moved into place.
+    let blocks = [
+        (
+            330.0,
+            15.0,
+            Vec3::new(265.0, 0.0, 295.0),
+            Color::new(0.0, 0.0, 0.0),
+        ),
+        (
+            165.0,
+            -18.0,
+            Vec3::new(130.0, 0.0, 65.0),
+            Color::new(1.0, 1.0, 1.0),
+        ),
+    ];
+    for (height, angle, offset, smoke_color) in blocks {
+        let block = make_box(
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(165.0, height, 165.0),
+            white,
+        );
+        let transform =
+            Transform::rotate(Vec3::new(0.0, 1.0, 0.0), angle).then(&Transform::translate(offset));
+        let block = Instance::new(Arc::new(block), transform);
+        if smoke {
+            let phase = materials.add(Material::new_isotropic(smoke_color));
+            world.add(Box::new(ConstantMedium::new(Arc::new(block), 0.01, phase)));
+        } else {
+            world.add(Box::new(block));
+        }
+    }
+
+    let mut camera = Camera::new(
+        1.0,
+        600,
+        200,
+        50,
+        40.0,
+        Point3::new(278.0, 278.0, -800.0),
+        Point3::new(278.0, 278.0, 0.0),
+        Point3::new(0.0, 1.0, 0.0),
+        0.0,
+        10.0,
+    );
+    camera.background = Background::Solid(Color::new(0.0, 0.0, 0.0));
+
+    Scene {
+        camera,
+        world,
+        materials,
+        post: PostProcess::default(),
+    }
+}
+
+fn quadrics() -> Scene {
+    let mut world = HittableList::new();
+    let mut materials = Materials::new();
+
+    let ground = materials.add(Material::new_lambertian_texture(Arc::new(
+        CheckerTexture::from_colors(0.5, Color::new(0.2, 0.3, 0.1), Color::new(0.9, 0.9, 0.9)),
+    )));
+    let red = materials.add(Material::new_lambertian(Color::new(0.7, 0.1, 0.1)));
+    let blue = materials.add(Material::new_lambertian(Color::new(0.1, 0.2, 0.6)));
+    let steel = materials.add(Material::new_metal(Color::new(0.8, 0.8, 0.85), 0.1));
+    let gold = materials.add(Material::new_metal(Color::new(0.9, 0.7, 0.3), 0.05));
+    let glass = materials.add(Material::new_dielectric(1.5));
+
+    world.add(Box::new(Plane::new(
+        Point3::new(0.0, 0.0, 0.0),
+        Vec3::new(0.0, 1.0, 0.0),
+        ground,
+    )));
+
+    // A capped post and an open pipe lying on its side.
+    world.add(Box::new(Cylinder::new(
+        Point3::new(-3.0, 0.0, -1.0),
+        Point3::new(-3.0, 2.0, -1.0),
+        0.5,
+        true,
+        red,
+    )));
+    world.add(Box::new(Cylinder::new(
+        Point3::new(-2.0, 0.4, 1.5),
+        Point3::new(0.0, 0.4, 2.5),
+        0.4,
+        false,
+        steel,
+    )));
+
+    world.add(Box::new(Cone::new(
+        Point3::new(-1.0, 0.0, -2.0),
+        Point3::new(-1.0, 2.5, -2.0),
+        0.8,
+        true,
+        blue,
+    )));
+    world.add(Box::new(Paraboloid::new(
+        Point3::new(1.0, 0.0, -1.0),
+        Point3::new(1.0, 1.5, -1.0),
+        1.0,
+        false,
+        steel,
+    )));
+    world.add(Box::new(Hyperboloid::new(
+        Point3::new(3.0, 0.0, -1.5),
+        Point3::new(3.0, 2.5, -1.5),
+        0.8,
+        0.4,
+        true,
+        glass,
+    )));
+
+    // One torus lying flat, one standing on its rim.
+    world.add(Box::new(Torus::new(
+        Point3::new(1.5, 0.25, 1.5),
+        Vec3::new(0.0, 1.0, 0.0),
+        0.7,
+        0.25,
+        gold,
+    )));
+    world.add(Box::new(Torus::new(
+        Point3::new(3.5, 0.9, 1.0),
+        Vec3::new(1.0, 0.0, 0.5),
+        0.7,
+        0.2,
+        red,
+    )));
+
+    let camera = Camera::new(
+        16.0 / 9.0,
+        400,
+        100,
+        50,
+        35.0,
+        Point3::new(0.0, 4.0, 10.0),
+        Point3::new(0.5, 0.8, 0.0),
+        Point3::new(0.0, 1.0, 0.0),
+        0.0,
+        10.0,
+    );
+
+    Scene {
+        camera,
+        world,
+        materials,
+        post: PostProcess::default(),
+    }
+}
@@ -1,27 +1,65 @@
-use crate::{interval::Interval, material::Material, vec3::Point3};
+use std::f64::consts::PI;
+
+use crate::{
+    aabb::Aabb,
+    hit_record::HitRecord,
+    hittable::Hittable,
+    interval::Interval,
+    material::MaterialId,
+    ray::Ray,
+    vec3::{Point3, Vec3},
+};
 
 #[derive(Clone)]
 pub struct Sphere {
+    /// The centre at time 0.
     pub center: Point3,
     pub radius: f64,
-    pub material: Material,
+    pub material: MaterialId,
+    // How far the centre travels between time 0 and time 1.
+    motion: Vec3,
+    bbox: Aabb,
 }
 
 impl Sphere {
-    pub fn new(center: Point3, radius: f64, material: Material) -> Self {
+    pub fn new(center: Point3, radius: f64, material: MaterialId) -> Self {
+        Sphere::new_moving(center, center, radius, material)
+    }
+
+    /// A sphere moving in a straight line from `center0` at time 0 to
+    /// `center1` at time 1, and resting there before and after.
+    pub fn new_moving(center0: Point3, center1: Point3, radius: f64, material: MaterialId) -> Self {
+        let rvec = Vec3::new(radius, radius, radius);
         Sphere {
-            center,
+            center: center0,
             radius,
             material,
+            motion: center1 - center0,
+            bbox: Aabb::surrounding(
+                &Aabb::from_points(center0 - rvec, center0 + rvec),
+                &Aabb::from_points(center1 - rvec, center1 + rvec),
+            ),
         }
     }
 
-    pub fn hit(
-        &self,
-        r: &crate::ray::Ray,
-        ray_t: &Interval,
-    ) -> Option<crate::hit_record::HitRecord> {
-        let oc = r.orig - self.center;
+    pub fn center_at(&self, time: f64) -> Point3 {
+        self.center + self.motion * time.clamp(0.0, 1.0)
+    }
+
+    /// Maps a point on the unit sphere to (u, v): u is the angle around the y
+    /// axis starting from -x, v is the angle from -y to +y, both in [0, 1].
+    fn get_sphere_uv(p: &Point3) -> (f64, f64) {
+        let theta = (-p.y).acos();
+        let phi = (-p.z).atan2(p.x) + PI;
+
+        (phi / (2.0 * PI), theta / PI)
+    }
+}
+
+impl Hittable for Sphere {
+    fn hit(&self, r: &Ray, ray_t: &Interval) -> Option<HitRecord> {
+        let center = self.center_at(r.time);
+        let oc = r.orig - center;
         let a = r.dir.length_squared();
         let half_b = oc.dot(r.dir);
         let c = oc.length_squared() - self.radius * self.radius;
@@ -43,12 +81,14 @@ impl Sphere {
 
         let p = r.at(root);
 
-        let outward_normal = (p - self.center) / self.radius;
-        Some(crate::hit_record::HitRecord::build(
-            root,
-            r,
-            &outward_normal,
-            self.material.clone(),
-        ))
+        let outward_normal = (p - center) / self.radius;
+        let mut rec = HitRecord::build(root, r, &outward_normal, self.material);
+        (rec.u, rec.v) = Sphere::get_sphere_uv(&outward_normal);
+
+        Some(rec)
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        self.bbox
     }
 }
@@ -0,0 +1,188 @@
+use std::sync::Arc;
+
+use serde::Deserialize;
+
+use crate::{
+    image::Image,
+    perlin::Perlin,
+    vec3::{Color, Point3},
+};
+
+/// A color that varies over a surface, looked up by surface coordinates
+/// `(u, v)` and hit point `p`.
+pub trait Texture {
+    fn value(&self, u: f64, v: f64, p: &Point3) -> Color;
+}
+
+pub struct SolidColor {
+    pub albedo: Color,
+}
+
+impl SolidColor {
+    pub fn new(albedo: Color) -> Self {
+        SolidColor { albedo }
+    }
+}
+
+impl Texture for SolidColor {
+    fn value(&self, _u: f64, _v: f64, _p: &Point3) -> Color {
+        self.albedo
+    }
+}
+
+/// A 3D checker pattern alternating between two textures in cubes of side
+/// `scale`.
+pub struct CheckerTexture {
+    inv_scale: f64,
+    even: Arc<dyn Texture + Send + Sync>,
+    odd: Arc<dyn Texture + Send + Sync>,
+}
+
+impl CheckerTexture {
+    pub fn new(
+        scale: f64,
+        even: Arc<dyn Texture + Send + Sync>,
+        odd: Arc<dyn Texture + Send + Sync>,
+    ) -> Self {
+        CheckerTexture {
+            inv_scale: 1.0 / scale,
+            even,
+            odd,
+        }
+    }
+
+    pub fn from_colors(scale: f64, even: Color, odd: Color) -> Self {
+        CheckerTexture::new(
+            scale,
+            Arc::new(SolidColor::new(even)),
+            Arc::new(SolidColor::new(odd)),
+        )
+    }
+}
+
+impl Texture for CheckerTexture {
+    fn value(&self, u: f64, v: f64, p: &Point3) -> Color {
+        let x = (self.inv_scale * p.x).floor() as i64;
+        let y = (self.inv_scale * p.y).floor() as i64;
+        let z = (self.inv_scale * p.z).floor() as i64;
+
+        if (x + y + z) % 2 == 0 {
+            self.even.value(u, v, p)
+        } else {
+            self.odd.value(u, v, p)
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WrapMode {
+    #[default]
+    Repeat,
+    Clamp,
+    Mirror,
+}
+
+impl WrapMode {
+    fn apply(&self, i: i64, size: u32) -> u32 {
+        let size = size as i64;
+        let wrapped = match self {
+            WrapMode::Repeat => i.rem_euclid(size),
+            WrapMode::Clamp => i.clamp(0, size - 1),
+            WrapMode::Mirror => {
+                let m = i.rem_euclid(2 * size);
+                if m < size {
+                    m
+                } else {
+                    2 * size - 1 - m
+                }
+            }
+        };
+        wrapped as u32
+    }
+}
+
+/// Maps an image onto the surface's (u, v) coordinates with bilinear
+/// filtering. `v = 0` is the bottom row of the image.
+pub struct ImageTexture {
+    image: Arc<Image>,
+    pub wrap: WrapMode,
+}
+
+impl ImageTexture {
+    pub fn new(image: Arc<Image>, wrap: WrapMode) -> Self {
+        ImageTexture { image, wrap }
+    }
+
+    fn texel(&self, x: i64, y: i64) -> Color {
+        self.image.pixel(
+            self.wrap.apply(x, self.image.width),
+            self.wrap.apply(y, self.image.height),
+        )
+    }
+}
+
+impl Texture for ImageTexture {
+    fn value(&self, u: f64, v: f64, _p: &Point3) -> Color {
+        if self.image.width == 0 || self.image.height == 0 {
+            return Color::new(0.0, 1.0, 1.0);
+        }
+
+        // Texel centers sit at half-integer coordinates.
+        let x = u * self.image.width as f64 - 0.5;
+        let y = (1.0 - v) * self.image.height as f64 - 0.5;
+
+        let x0 = x.floor();
+        let y0 = y.floor();
+        let fx = x - x0;
+        let fy = y - y0;
+        let (x0, y0) = (x0 as i64, y0 as i64);
+
+        let top = (1.0 - fx) * self.texel(x0, y0) + fx * self.texel(x0 + 1, y0);
+        let bottom = (1.0 - fx) * self.texel(x0, y0 + 1) + fx * self.texel(x0 + 1, y0 + 1);
+
+        (1.0 - fy) * top + fy * bottom
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NoiseKind {
+    /// Plain Perlin noise, remapped to [0, 1].
+    Smooth,
+    /// Sum of 7 octaves of noise magnitude.
+    Turbulence,
+    /// Sine stripes along z, phase-shifted by turbulence.
+    #[default]
+    Marble,
+}
+
+pub struct NoiseTexture {
+    noise: Perlin,
+    scale: f64,
+    kind: NoiseKind,
+}
+
+impl NoiseTexture {
+    pub fn new(scale: f64, kind: NoiseKind) -> Self {
+        NoiseTexture {
+            noise: Perlin::new(),
+            scale,
+            kind,
+        }
+    }
+}
+
+impl Texture for NoiseTexture {
+    fn value(&self, _u: f64, _v: f64, p: &Point3) -> Color {
+        let grey = match self.kind {
+            NoiseKind::Smooth => 0.5 * (1.0 + self.noise.noise(&(self.scale * *p))),
+            NoiseKind::Turbulence => self.noise.turb(&(self.scale * *p), 7),
+            NoiseKind::Marble => {
+                0.5 * (1.0 + (self.scale * p.z + 10.0 * self.noise.turb(p, 7)).sin())
+            }
+        };
+
+        Color::new(grey, grey, grey)
+    }
+}
@@ -0,0 +1,141 @@
+use serde::Deserialize;
+
+/// A rectangle of pixels rendered as one unit of work.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Tile {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// The order tiles are handed to the worker threads.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TileOrder {
+    /// Row by row from the top left, like reading a page.
+    Scanline,
+    /// Outwards from the centre of the image, where the subject usually is.
+    #[default]
+    Spiral,
+    /// Along a Hilbert curve, which keeps consecutive tiles next to each
+    /// other for better cache reuse.
+    Hilbert,
+}
+
+impl TileOrder {
+    pub const ALL: [TileOrder; 3] = [TileOrder::Scanline, TileOrder::Spiral, TileOrder::Hilbert];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            TileOrder::Scanline => "scanline",
+            TileOrder::Spiral => "spiral",
+            TileOrder::Hilbert => "hilbert",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<TileOrder> {
+        TileOrder::ALL
+            .into_iter()
+            .find(|order| order.name() == name)
+    }
+}
+
+/// Splits a `width` x `height` image into tiles of at most `size` pixels
+/// square, listed in `order`. Tiles on the right and bottom edges are cut
+/// short to fit the image.
+///
+/// ```
+/// use ray_tracing_in_a_weekend_rust::tile::{tiles, TileOrder};
+///
+/// let spiral = tiles(100, 50, 16, TileOrder::Spiral);
+/// assert_eq!(spiral.len(), 7 * 4);
+/// // The first tile is the one at the centre.
+/// assert_eq!((spiral[0].x, spiral[0].y), (48, 16));
+///
+/// let area: u32 = tiles(100, 50, 16, TileOrder::Hilbert)
+///     .iter()
+///     .map(|tile| tile.width * tile.height)
+///     .sum();
+/// assert_eq!(area, 100 * 50);
+/// ```
+pub fn tiles(width: u32, height: u32, size: u32, order: TileOrder) -> Vec<Tile> {
+    let size = size.max(1);
+    let columns = width.div_ceil(size);
+    let rows = height.div_ceil(size);
+
+    let grid: Vec<(u32, u32)> = match order {
+        TileOrder::Scanline => (0..rows)
+            .flat_map(|row| (0..columns).map(move |column| (column, row)))
+            .collect(),
+        TileOrder::Spiral => spiral(columns, rows),
+        TileOrder::Hilbert => {
+            let n = columns.max(rows).next_power_of_two();
+            let mut grid: Vec<_> = (0..rows)
+                .flat_map(|row| (0..columns).map(move |column| (column, row)))
+                .collect();
+            grid.sort_by_key(|&(column, row)| hilbert_index(n, column, row));
+            grid
+        }
+    };
+
+    grid.into_iter()
+        .map(|(column, row)| {
+            let (x, y) = (column * size, row * size);
+            Tile {
+                x,
+                y,
+                width: size.min(width - x),
+                height: size.min(height - y),
+            }
+        })
+        .collect()
+}
+
+/// Walks a square spiral out from the centre of a `columns` x `rows` grid,
+/// keeping the cells that fall inside it.
+fn spiral(columns: u32, rows: u32) -> Vec<(u32, u32)> {
+    let count = (columns * rows) as usize;
+    let mut cells = Vec::with_capacity(count);
+    let (mut x, mut y) = (((columns as i64) - 1) / 2, ((rows as i64) - 1) / 2);
+    let directions = [(1, 0), (0, 1), (-1, 0), (0, -1)];
+
+    // Legs grow by one every second turn: 1, 1, 2, 2, 3, 3, ...
+    let mut leg = 0;
+    while cells.len() < count {
+        let (dx, dy) = directions[leg % 4];
+        for _ in 0..leg / 2 + 1 {
+            if (0..columns as i64).contains(&x) && (0..rows as i64).contains(&y) {
+                cells.push((x as u32, y as u32));
+            }
+            x += dx;
+            y += dy;
+        }
+        leg += 1;
+    }
+
+    cells
+}
+
+/// Position of cell (`x`, `y`) along the Hilbert curve filling an `n` x `n`
+/// grid, `n` being a power of two.
+fn hilbert_index(n: u32, mut x: u32, mut y: u32) -> u64 {
+    let mut d = 0;
+    let mut s = n / 2;
+    while s > 0 {
+        let rx = (x & s > 0) as u32;
+        let ry = (y & s > 0) as u32;
+        d += s as u64 * s as u64 * ((3 * rx) ^ ry) as u64;
+
+        // Rotate the quadrant so the curve inside it runs the right way.
+        if ry == 0 {
+            if rx == 1 {
+                x = n - 1 - x;
+                y = n - 1 - y;
+            }
+            std::mem::swap(&mut x, &mut y);
+        }
+        s /= 2;
+    }
+    d
+}
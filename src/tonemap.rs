@@ -0,0 +1,183 @@
+use serde::Deserialize;
+
+use crate::{
+    image::{linear_to_srgb, luminance, Image},
+    rng::{self, Rng},
+    vec3::Color,
+};
+
+/// How radiance above 1 is brought into the displayable range.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ToneMap {
+    /// Cut each channel off at 1. Highlights clip to flat white.
+    #[default]
+    Clamp,
+    /// `L / (1 + L)` on luminance. Never quite reaches white.
+    Reinhard,
+    /// Reinhard with a white point: luminance at or above `white_point`
+    /// maps to white.
+    ExtendedReinhard,
+    /// Narkowicz's fit of the ACES filmic curve, per channel. Adds contrast
+    /// and desaturates bright colours towards white.
+    Aces,
+    /// An approximation of Blender's AgX: a log-encoded sigmoid applied in a
+    /// slightly narrowed gamut, which keeps very bright saturated colours from
+    /// skewing in hue.
+    Agx,
+}
+
+impl ToneMap {
+    pub const ALL: [ToneMap; 5] = [
+        ToneMap::Clamp,
+        ToneMap::Reinhard,
+        ToneMap::ExtendedReinhard,
+        ToneMap::Aces,
+        ToneMap::Agx,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ToneMap::Clamp => "clamp",
+            ToneMap::Reinhard => "reinhard",
+            ToneMap::ExtendedReinhard => "extended_reinhard",
+            ToneMap::Aces => "aces",
+            ToneMap::Agx => "agx",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<ToneMap> {
+        ToneMap::ALL.into_iter().find(|op| op.name() == name)
+    }
+}
+
+/// Turns a linear HDR image into 8-bit sRGB for display: exposure, then tone
+/// mapping, then the sRGB transfer function and quantization. Float formats
+/// skip this and keep the linear values.
+#[derive(Clone, Copy, Debug, Deserialize)]
+#[serde(deny_unknown_fields, default)]
+pub struct PostProcess {
+    /// Exposure adjustment in stops: each step doubles the brightness.
+    pub exposure: f64,
+    pub tone_map: ToneMap,
+    /// Luminance that maps to white with [`ToneMap::ExtendedReinhard`].
+    pub white_point: f64,
+    /// Add up to one step of triangular noise before quantizing, trading
+    /// banding in smooth gradients for fine grain.
+    pub dither: bool,
+}
+
+impl Default for PostProcess {
+    fn default() -> Self {
+        PostProcess {
+            exposure: 0.0,
+            tone_map: ToneMap::default(),
+            white_point: 4.0,
+            dither: false,
+        }
+    }
+}
+
+impl PostProcess {
+    /// Maps scene-linear `c` to display-linear values in [0, 1].
+    pub fn apply(&self, c: Color) -> Color {
+        let c = c * self.exposure.exp2();
+        let c = Color::new(c.x.max(0.0), c.y.max(0.0), c.z.max(0.0));
+
+        let mapped = match self.tone_map {
+            ToneMap::Clamp => c,
+            ToneMap::Reinhard => scale_luminance(c, |l| l / (1.0 + l)),
+            ToneMap::ExtendedReinhard => {
+                let white_sq = self.white_point * self.white_point;
+                scale_luminance(c, |l| l * (1.0 + l / white_sq) / (1.0 + l))
+            }
+            ToneMap::Aces => Color::new(aces(c.x), aces(c.y), aces(c.z)),
+            ToneMap::Agx => agx(c),
+        };
+
+        Color::new(
+            mapped.x.clamp(0.0, 1.0),
+            mapped.y.clamp(0.0, 1.0),
+            mapped.z.clamp(0.0, 1.0),
+        )
+    }
+
+    /// The image as 8-bit sRGB, three bytes per pixel, top row first.
+    pub fn srgb8(&self, image: &Image) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(image.pixels().len() * 3);
+        for y in 0..image.height {
+            for x in 0..image.width {
+                let c = self.apply(image.pixel(x, y));
+                // Seeded by position, so dithered output is reproducible.
+                let mut rng = Rng::new(rng::hash(&[x as u64, y as u64]));
+                for v in [c.x, c.y, c.z] {
+                    let mut encoded = linear_to_srgb(v) * 255.0;
+                    if self.dither {
+                        encoded += rng.next_f64() + rng.next_f64() - 1.0;
+                    }
+                    bytes.push(encoded.round().clamp(0.0, 255.0) as u8);
+                }
+            }
+        }
+        bytes
+    }
+}
+
+/// Scales `c` so its luminance becomes `curve(luminance)`, keeping its hue.
+fn scale_luminance(c: Color, curve: impl Fn(f64) -> f64) -> Color {
+    let l = luminance(c);
+    if l <= 0.0 {
+        return c;
+    }
+    c * (curve(l) / l)
+}
+
+/// Krzysztof Narkowicz's rational fit of the ACES RRT + ODT.
+fn aces(x: f64) -> f64 {
+    let (a, b, c, d, e) = (2.51, 0.03, 2.43, 0.59, 0.14);
+    (x * (a * x + b)) / (x * (c * x + d) + e)
+}
+
+/// AgX in the style of Benjamin Wrensch's minimal version: an inset into a
+/// narrower gamut, a log2 encoding over about 16.5 stops, a polynomial
+/// sigmoid, and the matching outset.
+fn agx(c: Color) -> Color {
+    const MIN_EV: f64 = -12.47393;
+    const MAX_EV: f64 = 4.026069;
+
+    let inset = mul(
+        [
+            [0.842479062253094, 0.0784335999999992, 0.0792237451477643],
+            [0.0423282422610123, 0.878468636469772, 0.0791661274605434],
+            [0.0423756549057051, 0.0784336, 0.879142973793104],
+        ],
+        c,
+    );
+
+    let curve = |v: f64| {
+        let x = ((v.max(1e-10).log2() - MIN_EV) / (MAX_EV - MIN_EV)).clamp(0.0, 1.0);
+        let x2 = x * x;
+        let x4 = x2 * x2;
+        15.5 * x4 * x2 - 40.14 * x4 * x + 31.96 * x4 - 6.868 * x2 * x + 0.4298 * x2 + 0.1191 * x
+            - 0.00232
+    };
+    let curved = Color::new(curve(inset.x), curve(inset.y), curve(inset.z));
+
+    let outset = mul(
+        [
+            [1.19687900512017, -0.0980208811401368, -0.0990297440797205],
+            [-0.0528968517574562, 1.15190312990417, -0.0989611768448433],
+            [-0.0529716355144438, -0.0980434501171241, 1.15107367264116],
+        ],
+        curved,
+    );
+
+    // The sigmoid's output is display-encoded with a 2.2 gamma.
+    let linear = |v: f64| v.max(0.0).powf(2.2);
+    Color::new(linear(outset.x), linear(outset.y), linear(outset.z))
+}
+
+fn mul(m: [[f64; 3]; 3], c: Color) -> Color {
+    let row = |r: [f64; 3]| r[0] * c.x + r[1] * c.y + r[2] * c.z;
+    Color::new(row(m[0]), row(m[1]), row(m[2]))
+}
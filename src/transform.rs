@@ -0,0 +1,138 @@
+use crate::{
+    aabb::Aabb,
+    mat4::Mat4,
+    vec3::{Point3, Vec3},
+};
+
+/// An invertible affine transform, kept together with its inverse so both
+/// directions cost one matrix multiply.
+///
+/// ```
+/// use ray_tracing_in_a_weekend_rust::{transform::Transform, Point3, Vec3};
+///
+/// let t = Transform::rotate(Vec3::new(0.0, 1.0, 0.0), 90.0)
+///     .then(&Transform::translate(Vec3::new(0.0, 2.0, 0.0)));
+/// let p = t.point(Point3::new(1.0, 0.0, 0.0));
+/// assert!((p - Point3::new(0.0, 2.0, -1.0)).near_zero());
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Transform {
+    matrix: Mat4,
+    inverse: Mat4,
+}
+
+impl Transform {
+    pub const IDENTITY: Transform = Transform {
+        matrix: Mat4::IDENTITY,
+        inverse: Mat4::IDENTITY,
+    };
+
+    /// Wraps an affine `matrix`, or returns `None` if it cannot be inverted.
+    pub fn new(matrix: Mat4) -> Option<Self> {
+        Some(Transform {
+            matrix,
+            inverse: matrix.inverse()?,
+        })
+    }
+
+    pub fn translate(offset: Vec3) -> Self {
+        Transform {
+            matrix: Mat4::translation(offset),
+            inverse: Mat4::translation(-offset),
+        }
+    }
+
+    /// Scales along each axis. Panics if a factor is zero.
+    pub fn scale(factors: Vec3) -> Self {
+        assert!(
+            factors.x != 0.0 && factors.y != 0.0 && factors.z != 0.0,
+            "Scale factors must be non-zero"
+        );
+        Transform {
+            matrix: Mat4::scaling(factors),
+            inverse: Mat4::scaling(Vec3::new(1.0 / factors.x, 1.0 / factors.y, 1.0 / factors.z)),
+        }
+    }
+
+    /// Rotates by `degrees` around `axis` through the origin.
+    pub fn rotate(axis: Vec3, degrees: f64) -> Self {
+        let matrix = Mat4::rotation(axis, degrees);
+        Transform {
+            matrix,
+            inverse: matrix.transpose(),
+        }
+    }
+
+    /// `self` followed by `next`.
+    pub fn then(&self, next: &Transform) -> Transform {
+        Transform {
+            matrix: next.matrix * self.matrix,
+            inverse: self.inverse * next.inverse,
+        }
+    }
+
+    pub fn inverse(&self) -> Transform {
+        Transform {
+            matrix: self.inverse,
+            inverse: self.matrix,
+        }
+    }
+
+    pub fn matrix(&self) -> &Mat4 {
+        &self.matrix
+    }
+
+    pub fn inverse_matrix(&self) -> &Mat4 {
+        &self.inverse
+    }
+
+    pub fn point(&self, p: Point3) -> Point3 {
+        self.matrix.transform_point(p)
+    }
+
+    pub fn vector(&self, v: Vec3) -> Vec3 {
+        self.matrix.transform_vector(v)
+    }
+
+    /// Transforms a surface normal by the inverse transpose, which keeps it
+    /// perpendicular to the transformed surface. The result is not
+    /// normalized.
+    pub fn normal(&self, n: Vec3) -> Vec3 {
+        let m = &self.inverse.m;
+        Vec3::new(
+            m[0][0] * n.x + m[1][0] * n.y + m[2][0] * n.z,
+            m[0][1] * n.x + m[1][1] * n.y + m[2][1] * n.z,
+            m[0][2] * n.x + m[1][2] * n.y + m[2][2] * n.z,
+        )
+    }
+
+    /// A box enclosing `bbox` after the transform. Unbounded boxes stay
+    /// unbounded.
+    pub fn bounding_box(&self, bbox: &Aabb) -> Aabb {
+        if bbox.is_empty() {
+            return Aabb::EMPTY;
+        }
+        if !bbox.surface_area().is_finite() {
+            return Aabb::UNIVERSE;
+        }
+
+        let mut lo = Point3::new(f64::INFINITY, f64::INFINITY, f64::INFINITY);
+        let mut hi = -lo;
+        for x in [bbox.x.min, bbox.x.max] {
+            for y in [bbox.y.min, bbox.y.max] {
+                for z in [bbox.z.min, bbox.z.max] {
+                    let p = self.point(Point3::new(x, y, z));
+                    lo = Point3::new(lo.x.min(p.x), lo.y.min(p.y), lo.z.min(p.z));
+                    hi = Point3::new(hi.x.max(p.x), hi.y.max(p.y), hi.z.max(p.z));
+                }
+            }
+        }
+        Aabb::from_points(lo, hi)
+    }
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Transform::IDENTITY
+    }
+}
@@ -0,0 +1,228 @@
+use std::sync::Arc;
+
+use crate::{
+    aabb::Aabb,
+    bvh::Bvh,
+    hit_record::HitRecord,
+    hittable::Hittable,
+    hittable_list::HittableList,
+    interval::Interval,
+    material::MaterialId,
+    ray::Ray,
+    vec3::{Point3, Vec3},
+};
+
+struct MeshData {
+    positions: Vec<Point3>,
+    normals: Option<Vec<Vec3>>,
+    uvs: Option<Vec<(f64, f64)>>,
+    indices: Vec<[u32; 3]>,
+    material: MaterialId,
+}
+
+/// An indexed triangle mesh. Vertex buffers are shared by every triangle of
+/// the mesh, and the triangles are kept in their own BVH.
+pub struct TriangleMesh {
+    data: Arc<MeshData>,
+    bvh: Bvh,
+}
+
+impl TriangleMesh {
+    /// Builds a mesh from vertex `positions` and triangles given as
+    /// `indices` into them, with optional per-vertex `normals` and `uvs`.
+    ///
+    /// # Panics
+    ///
+    /// If an index is out of range, or if `normals` or `uvs` do not have one
+    /// entry per vertex.
+    pub fn new(
+        positions: Vec<Point3>,
+        indices: Vec<[u32; 3]>,
+        normals: Option<Vec<Vec3>>,
+        uvs: Option<Vec<(f64, f64)>>,
+        material: MaterialId,
+    ) -> Self {
+        let vertex_count = positions.len();
+        assert!(
+            indices
+                .iter()
+                .flatten()
+                .all(|&i| (i as usize) < vertex_count),
+            "Triangle index out of range ({} vertices)",
+            vertex_count
+        );
+        if let Some(normals) = &normals {
+            assert_eq!(
+                normals.len(),
+                vertex_count,
+                "Expected one normal per vertex"
+            );
+        }
+        if let Some(uvs) = &uvs {
+            assert_eq!(uvs.len(), vertex_count, "Expected one uv per vertex");
+        }
+
+        let data = Arc::new(MeshData {
+            positions,
+            normals,
+            uvs,
+            indices,
+            material,
+        });
+
+        let mut triangles = HittableList::new();
+        for index in 0..data.indices.len() {
+            triangles.add(Box::new(MeshTriangle {
+                mesh: data.clone(),
+                index: index as u32,
+            }));
+        }
+
+        TriangleMesh {
+            data,
+            bvh: Bvh::new(triangles),
+        }
+    }
+
+    pub fn vertex_count(&self) -> usize {
+        self.data.positions.len()
+    }
+
+    pub fn triangle_count(&self) -> usize {
+        self.data.indices.len()
+    }
+}
+
+impl Hittable for TriangleMesh {
+    fn hit(&self, r: &Ray, ray_t: &Interval) -> Option<HitRecord> {
+        self.bvh.hit(r, ray_t)
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        self.bvh.bounding_box()
+    }
+}
+
+struct MeshTriangle {
+    mesh: Arc<MeshData>,
+    index: u32,
+}
+
+impl MeshTriangle {
+    fn vertex_indices(&self) -> [usize; 3] {
+        let [a, b, c] = self.mesh.indices[self.index as usize];
+        [a as usize, b as usize, c as usize]
+    }
+}
+
+impl Hittable for MeshTriangle {
+    fn hit(&self, r: &Ray, ray_t: &Interval) -> Option<HitRecord> {
+        let [i0, i1, i2] = self.vertex_indices();
+        let p0 = self.mesh.positions[i0];
+        let p1 = self.mesh.positions[i1];
+        let p2 = self.mesh.positions[i2];
+
+        let (t, b0, b1, b2) = intersect_triangle(r, ray_t, p0, p1, p2)?;
+
+        let outward_normal = (p1 - p0).cross(p2 - p0).unit_vector();
+        let mut rec = HitRecord::build(t, r, &outward_normal, self.mesh.material);
+
+        if let Some(normals) = &self.mesh.normals {
+            let shading_normal =
+                (b0 * normals[i0] + b1 * normals[i1] + b2 * normals[i2]).unit_vector();
+            rec.normal = if rec.front_face {
+                shading_normal
+            } else {
+                -shading_normal
+            };
+        }
+
+        if let Some(uvs) = &self.mesh.uvs {
+            rec.u = b0 * uvs[i0].0 + b1 * uvs[i1].0 + b2 * uvs[i2].0;
+            rec.v = b0 * uvs[i0].1 + b1 * uvs[i1].1 + b2 * uvs[i2].1;
+        } else {
+            rec.u = b1;
+            rec.v = b2;
+        }
+
+        Some(rec)
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        let [i0, i1, i2] = self.vertex_indices();
+        let p = &self.mesh.positions;
+        Aabb::surrounding(
+            &Aabb::from_points(p[i0], p[i1]),
+            &Aabb::from_points(p[i2], p[i2]),
+        )
+    }
+}
+
+/// Watertight ray/triangle intersection (Woop, Benthin and Wald, 2013).
+///
+/// Returns the ray parameter and the barycentric weights of `p0`, `p1` and
+/// `p2`. Rays crossing a shared edge hit at least one of the adjacent
+/// triangles, so closed meshes have no cracks.
+pub fn intersect_triangle(
+    r: &Ray,
+    ray_t: &Interval,
+    p0: Point3,
+    p1: Point3,
+    p2: Point3,
+) -> Option<(f64, f64, f64, f64)> {
+    // Permute axes so that z is the dominant ray direction.
+    let d = r.dir;
+    let kz: u8 = if d.x.abs() > d.y.abs() && d.x.abs() > d.z.abs() {
+        0
+    } else if d.y.abs() > d.z.abs() {
+        1
+    } else {
+        2
+    };
+    let (mut kx, mut ky) = ((kz + 1) % 3, (kz + 2) % 3);
+    if d[kz] < 0.0 {
+        std::mem::swap(&mut kx, &mut ky);
+    }
+
+    // Shear so the ray points down +z.
+    let sz = 1.0 / d[kz];
+    let sx = d[kx] * sz;
+    let sy = d[ky] * sz;
+
+    let a = p0 - r.orig;
+    let b = p1 - r.orig;
+    let c = p2 - r.orig;
+
+    let ax = a[kx] - sx * a[kz];
+    let ay = a[ky] - sy * a[kz];
+    let bx = b[kx] - sx * b[kz];
+    let by = b[ky] - sy * b[kz];
+    let cx = c[kx] - sx * c[kz];
+    let cy = c[ky] - sy * c[kz];
+
+    // Scaled barycentrics, as 2D edge functions.
+    let u = cx * by - cy * bx;
+    let v = ax * cy - ay * cx;
+    let w = bx * ay - by * ax;
+
+    if (u < 0.0 || v < 0.0 || w < 0.0) && (u > 0.0 || v > 0.0 || w > 0.0) {
+        return None;
+    }
+
+    let det = u + v + w;
+    if det == 0.0 {
+        return None;
+    }
+
+    let az = sz * a[kz];
+    let bz = sz * b[kz];
+    let cz = sz * c[kz];
+
+    let inv_det = 1.0 / det;
+    let t = (u * az + v * bz + w * cz) * inv_det;
+    if !ray_t.surrounds(t) {
+        return None;
+    }
+
+    Some((t, u * inv_det, v * inv_det, w * inv_det))
+}
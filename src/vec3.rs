@@ -1,4 +1,6 @@
-use std::{cmp::min, ops};
+use std::ops;
+
+use crate::rng::Rng;
 
 #[derive(Copy, Clone, Debug)]
 pub struct Vec3 {
@@ -7,10 +9,6 @@ pub struct Vec3 {
     pub z: f64,
 }
 
-fn linear_to_gamma(linear_component: f64) -> f64 {
-    linear_component.sqrt()
-}
-
 impl Vec3 {
     pub const fn new(x: f64, y: f64, z: f64) -> Self {
         Vec3 { x, y, z }
@@ -40,61 +38,55 @@ impl Vec3 {
         self / self.length()
     }
 
+    /// Two unit vectors perpendicular to `self` and to each other, ordered so
+    /// that `u.cross(v)` points along `self`.
+    pub fn orthonormal_basis(self) -> (Vec3, Vec3) {
+        let w = self.unit_vector();
+        let a = if w.x.abs() > 0.9 {
+            Vec3::new(0.0, 1.0, 0.0)
+        } else {
+            Vec3::new(1.0, 0.0, 0.0)
+        };
+        let u = a.cross(w).unit_vector();
+        (u, w.cross(u))
+    }
+
     pub fn near_zero(&self) -> bool {
         let s = 1.0e-8;
         (self.x.abs() < s) && (self.y.abs() < s) && (self.z.abs() < s)
     }
 
-    pub fn to_color_string(&self, samples_per_pixel: u32) -> String {
-        let scale = 1.0 / samples_per_pixel as f64;
-
-        let r = (self.x * scale).clamp(0.000, 0.999);
-        let g = (self.y * scale).clamp(0.000, 0.999);
-        let b = (self.z * scale).clamp(0.000, 0.999);
-
-        let r = linear_to_gamma(r);
-        let g = linear_to_gamma(g);
-        let b = linear_to_gamma(b);
-
-        format!(
-            "{} {} {}",
-            (r * 256.0) as u32,
-            (g * 256.0) as u32,
-            (b * 256.0) as u32,
-        )
-    }
-
-    pub fn random() -> Vec3 {
+    pub fn random(rng: &mut Rng) -> Vec3 {
         Vec3 {
-            x: rand::random(),
-            y: rand::random(),
-            z: rand::random(),
+            x: rng.next_f64(),
+            y: rng.next_f64(),
+            z: rng.next_f64(),
         }
     }
 
-    pub fn random_range(min: f64, max: f64) -> Vec3 {
+    pub fn random_range(rng: &mut Rng, min: f64, max: f64) -> Vec3 {
         Vec3 {
-            x: rand::random::<f64>() * (max - min) + min,
-            y: rand::random::<f64>() * (max - min) + min,
-            z: rand::random::<f64>() * (max - min) + min,
+            x: rng.range(min, max),
+            y: rng.range(min, max),
+            z: rng.range(min, max),
         }
     }
 
-    pub fn random_in_unit_sphere() -> Vec3 {
+    pub fn random_in_unit_sphere(rng: &mut Rng) -> Vec3 {
         loop {
-            let p = Vec3::random_range(-1.0, 1.0);
+            let p = Vec3::random_range(rng, -1.0, 1.0);
             if p.length_squared() < 1.0 {
                 return p;
             }
         }
     }
 
-    pub fn random_unit_vector() -> Vec3 {
-        Vec3::random_in_unit_sphere().unit_vector()
+    pub fn random_unit_vector(rng: &mut Rng) -> Vec3 {
+        Vec3::random_in_unit_sphere(rng).unit_vector()
     }
 
-    pub fn random_on_hemisphere(normal: &Vec3) -> Vec3 {
-        let on_unit_sphere = Vec3::random_in_unit_sphere();
+    pub fn random_on_hemisphere(rng: &mut Rng, normal: &Vec3) -> Vec3 {
+        let on_unit_sphere = Vec3::random_in_unit_sphere(rng);
         if on_unit_sphere.dot(*normal) > 0.0 {
             on_unit_sphere
         } else {
@@ -118,11 +110,11 @@ impl Vec3 {
         r_out_perp + r_out_parallel
     }
 
-    pub fn random_in_unit_disk() -> Vec3 {
+    pub fn random_in_unit_disk(rng: &mut Rng) -> Vec3 {
         loop {
             let p = Vec3 {
-                x: rand::random::<f64>() * 2.0 - 1.0,
-                y: rand::random::<f64>() * 2.0 - 1.0,
+                x: rng.range(-1.0, 1.0),
+                y: rng.range(-1.0, 1.0),
                 z: 0.0,
             };
             if p.length_squared() < 1.0 {